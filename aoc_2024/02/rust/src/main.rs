@@ -1,5 +1,6 @@
 use std::env;
 use std::io;
+use std::thread;
 
 fn is_safe_diff(report: impl Iterator<Item = i32>) -> bool {
     let mut diffs = Vec::<i32>::new();
@@ -72,6 +73,48 @@ fn is_safe_big_loop(report: impl Iterator<Item = i32>) -> bool {
     return true;
 }
 
+fn is_safe_report(line: &str, part: usize) -> bool {
+    let report = line
+        .split(" ")
+        .map(|e| e.parse::<i32>().expect("unable to parse line token as int"));
+    return match part {
+        1 => is_safe_diff(report),
+        2 => {
+            // Split into all possible subsequences
+            for skip_idx in 0..report.clone().count() {
+                if is_safe_diff(
+                    report
+                        .clone()
+                        .enumerate()
+                        .filter(|(i, _)| *i != skip_idx)
+                        .map(|(_, e)| e),
+                ) {
+                    return true;
+                }
+            }
+            return false;
+        }
+        _ => panic!("unsupported part {}", part),
+    };
+}
+
+// Each report is independent, so they can be split into chunks and checked on
+// separate threads. Counting is order-independent, so the answer is the same
+// however many threads there are.
+fn count_safe(lines: &[String], part: usize, jobs: usize) -> usize {
+    if jobs <= 1 {
+        return lines.iter().filter(|l| is_safe_report(l, part)).count();
+    }
+    let chunk_size = lines.len().div_ceil(jobs).max(1);
+    thread::scope(|s| {
+        let handles: Vec<_> = lines
+            .chunks(chunk_size)
+            .map(|chunk| s.spawn(move || count_safe(chunk, part, 1)))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    })
+}
+
 fn main() {
     println!("AoC 2024 Day 2 - Rust");
 
@@ -80,36 +123,22 @@ fn main() {
         Ok(num) => num,
         Err(e) => panic!("unable to parse part flag: {:?}", e),
     };
+    // Optionally, how many threads to check reports on (just the one by
+    // default, or 0 for one per core).
+    let jobs = match args.next() {
+        Some(arg) => arg.parse::<usize>().expect("unable to parse jobs"),
+        None => 1,
+    };
+    let jobs = match jobs {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
 
-    let solution: usize = io::stdin()
+    let lines: Vec<String> = io::stdin()
         .lines()
-        .map(|s| {
-            let line = s.expect("did not get expected line");
-            let report = line
-                .split(" ")
-                .map(|e| e.parse::<i32>().expect("unable to parse line token as int"));
-            return match part {
-                1 => is_safe_diff(report),
-                2 => {
-                    // Split into all possible subsequences
-                    for skip_idx in 0..report.clone().count() {
-                        if is_safe_diff(
-                            report
-                                .clone()
-                                .enumerate()
-                                .filter(|(i, _)| *i != skip_idx)
-                                .map(|(_, e)| e),
-                        ) {
-                            return true;
-                        }
-                    }
-                    return false;
-                }
-                _ => panic!("unsupported part {}", part),
-            };
-        })
-        .filter(|is_safe| *is_safe)
-        .count();
+        .map(|s| s.expect("did not get expected line"))
+        .collect();
+    let solution = count_safe(&lines, part, jobs);
 
     println!("Output: {}", solution);
 }
@@ -0,0 +1,157 @@
+// Answer is the integer a puzzle boils down to. It's usually small, but some
+// days multiply or sum their way past what fits in a machine integer, so
+// Answers promote themselves to a BigInt instead of overflowing.
+
+use num_bigint::BigInt;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::{Product, Sum};
+use std::ops::{Add, Mul};
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Answer {
+    Small(i128),
+    Big(BigInt),
+}
+
+impl Answer {
+    fn big(&self) -> BigInt {
+        match self {
+            Answer::Small(n) => BigInt::from(*n),
+            Answer::Big(n) => n.clone(),
+        }
+    }
+
+    // shrink goes back to Small whenever a BigInt fits in one again.
+    fn shrink(n: BigInt) -> Answer {
+        match i128::try_from(&n) {
+            Ok(small) => Answer::Small(small),
+            Err(_) => Answer::Big(n),
+        }
+    }
+
+    // combine applies an operation to two Answers, using the checked i128
+    // version when both are small and it doesn't overflow, and falling back
+    // to the BigInt version otherwise.
+    pub(crate) fn combine(
+        &self,
+        other: &Answer,
+        small: impl Fn(i128, i128) -> Option<i128>,
+        big: impl Fn(BigInt, BigInt) -> BigInt,
+    ) -> Answer {
+        if let (Answer::Small(a), Answer::Small(b)) = (self, other) {
+            if let Some(out) = small(*a, *b) {
+                return Answer::Small(out);
+            }
+        }
+        Answer::shrink(big(self.big(), other.big()))
+    }
+
+    // small returns the Answer as an i128, failing loudly if it's too big for
+    // callers that can't cope with anything larger.
+    pub(crate) fn small(&self) -> Result<i128, String> {
+        match self {
+            Answer::Small(n) => Ok(*n),
+            Answer::Big(n) => Err(format!("{n} doesn't fit in an i128")),
+        }
+    }
+
+    pub(crate) fn is_negative(&self) -> bool {
+        *self < Answer::from(0)
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        *self == Answer::from(0)
+    }
+
+    // bits is how many bits the Answer's magnitude takes up.
+    pub(crate) fn bits(&self) -> u64 {
+        self.big().bits()
+    }
+}
+
+macro_rules! answer_from {
+    ($($t:ty),*) => {
+        $(impl From<$t> for Answer {
+            fn from(value: $t) -> Self {
+                Answer::Small(value.into())
+            }
+        })*
+    };
+}
+answer_from!(u8, u32, u64, i32, i64, i128);
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Answer::Small(value as i128)
+    }
+}
+
+impl From<u128> for Answer {
+    fn from(value: u128) -> Self {
+        Answer::shrink(BigInt::from(value))
+    }
+}
+
+impl FromStr for Answer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        BigInt::from_str(s)
+            .map(Answer::shrink)
+            .map_err(|e| format!("can't parse {s:?} as a number: {e}"))
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Small(n) => write!(f, "{n}"),
+            Answer::Big(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+impl Ord for Answer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Answer::Small(a), Answer::Small(b)) => a.cmp(b),
+            _ => self.big().cmp(&other.big()),
+        }
+    }
+}
+
+impl PartialOrd for Answer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Answer {
+    type Output = Answer;
+
+    fn add(self, rhs: Answer) -> Answer {
+        self.combine(&rhs, i128::checked_add, |a, b| a + b)
+    }
+}
+
+impl Mul for Answer {
+    type Output = Answer;
+
+    fn mul(self, rhs: Answer) -> Answer {
+        self.combine(&rhs, i128::checked_mul, |a, b| a * b)
+    }
+}
+
+impl Sum for Answer {
+    fn sum<I: Iterator<Item = Answer>>(iter: I) -> Answer {
+        iter.fold(Answer::from(0), |acc, a| acc + a)
+    }
+}
+
+impl Product for Answer {
+    fn product<I: Iterator<Item = Answer>>(iter: I) -> Answer {
+        iter.fold(Answer::from(1), |acc, a| acc * a)
+    }
+}
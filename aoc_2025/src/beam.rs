@@ -0,0 +1,245 @@
+// Shared machinery for "beam through a grid" puzzles (day 7, and whatever
+// variant of it comes next). A puzzle describes its grid cells by
+// implementing Cell, and Layout takes care of propagating beams and counting
+// the timelines that make it out the other side.
+
+use crate::parse;
+use crate::parser;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Heading {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Heading {
+    const ALL: [Heading; 4] = [Heading::Up, Heading::Down, Heading::Left, Heading::Right];
+
+    pub(crate) fn is_vertical(&self) -> bool {
+        matches!(self, Heading::Up | Heading::Down)
+    }
+
+    pub(crate) fn turn_left(&self) -> Heading {
+        match self {
+            Heading::Up => Heading::Left,
+            Heading::Left => Heading::Down,
+            Heading::Down => Heading::Right,
+            Heading::Right => Heading::Up,
+        }
+    }
+
+    pub(crate) fn turn_right(&self) -> Heading {
+        self.turn_left().turn_left().turn_left()
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Heading::Up => 0,
+            Heading::Down => 1,
+            Heading::Left => 2,
+            Heading::Right => 3,
+        }
+    }
+}
+
+// Exit describes one beam leaving a cell: it moves one step `towards` some
+// side, and from then on travels with `heading`. These are usually the same,
+// but day 7's splitters shift a beam sideways while it keeps falling.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Exit {
+    pub(crate) towards: Heading,
+    pub(crate) heading: Heading,
+}
+
+impl Exit {
+    pub(crate) fn straight(heading: Heading) -> Exit {
+        Exit {
+            towards: heading,
+            heading,
+        }
+    }
+}
+
+pub(crate) trait Cell: Sized {
+    fn parse(c: char) -> Result<Self, String>;
+
+    // start returns the heading of the beam that this cell emits, if any.
+    fn start(&self) -> Option<Heading>;
+
+    // exits returns the beams that leave this cell when one enters it
+    // travelling with the given heading. An empty list absorbs the beam, and
+    // more than one counts as a split.
+    fn exits(&self, heading: Heading) -> Vec<Exit>;
+}
+
+// A Beam is a cell position, and the heading of the beam inside it.
+type Beam = ((usize, usize), Heading);
+
+// Trace is everything a single pass over a Layout tells us: how many distinct
+// cells split a beam, and how many timelines leave the grid from each edge
+// cell, along with the cell and the side they leave through.
+#[derive(Debug)]
+pub(crate) struct Trace {
+    pub(crate) splits: usize,
+    pub(crate) exits: Vec<(Beam, u64)>,
+}
+
+impl Trace {
+    // leaving returns the timeline counts leaving through the given side, by
+    // position of the cell they leave from.
+    pub(crate) fn leaving(
+        &self,
+        side: Heading,
+    ) -> impl Iterator<Item = ((usize, usize), u64)> + '_ {
+        self.exits
+            .iter()
+            .filter(move |((_, h), _)| *h == side)
+            .map(|((pos, _), count)| (*pos, *count))
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Layout<C: Cell> {
+    pub(crate) rows: Vec<Vec<C>>,
+}
+
+impl<C: Cell> Layout<C> {
+    pub(crate) fn parse(input: parse::Input) -> Result<Layout<C>, String> {
+        let text = parser::read(input)?;
+        let rows = parser::parse_all(parser::grid(C::parse), &text)?;
+        let width = rows.first().map(|r| r.len()).unwrap_or(0);
+        if let Some(r) = rows.iter().position(|row| row.len() != width) {
+            return Err(format!("line {}: row isn't {width} cells wide", r + 1));
+        }
+        Ok(Layout { rows })
+    }
+
+    pub(crate) fn width(&self) -> usize {
+        self.rows.first().map(|r| r.len()).unwrap_or(0)
+    }
+
+    fn index(&self, ((r, c), h): Beam) -> usize {
+        (r * self.width() + c) * Heading::ALL.len() + h.index()
+    }
+
+    fn step(&self, (r, c): (usize, usize), towards: Heading) -> Option<(usize, usize)> {
+        let next = match towards {
+            Heading::Up => (r.checked_sub(1)?, c),
+            Heading::Down => (r + 1, c),
+            Heading::Left => (r, c.checked_sub(1)?),
+            Heading::Right => (r, c + 1),
+        };
+        (next.0 < self.rows.len() && next.1 < self.width()).then_some(next)
+    }
+
+    fn starts(&self) -> Vec<Beam> {
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(r, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter_map(move |(c, cell)| cell.start().map(|h| ((r, c), h)))
+            })
+            .collect()
+    }
+
+    // trace propagates every beam from its start, then counts timelines by
+    // pushing counts through the beams in topological order (so a beam is
+    // only passed on once everything feeding into it has been counted). It's
+    // all iterative, and linear in the number of cells. A beam that can loop
+    // back on itself has infinitely many timelines, which is an error.
+    pub(crate) fn trace(&self) -> Result<Trace, String> {
+        let starts = self.starts();
+
+        // First, find every reachable beam, and the beams each one feeds.
+        // Ok(beam) lands in the grid, Err((pos, side)) leaves it from pos.
+        // Everything's kept in Vecs indexed by beam (or cell), since the grid
+        // is dense enough that hashing would cost more than it saves.
+        let beams = self.rows.len() * self.width() * Heading::ALL.len();
+        let mut feeds: Vec<Option<Vec<Result<Beam, Beam>>>> = vec![None; beams];
+        let mut reached = 0;
+        let mut indegree = vec![0_usize; beams];
+        let mut split_cells = vec![false; self.rows.len() * self.width()];
+        let mut splits = 0;
+        let mut pending = starts.clone();
+        while let Some(beam) = pending.pop() {
+            if feeds[self.index(beam)].is_some() {
+                continue;
+            }
+            let ((r, c), heading) = beam;
+            let exits = self.rows[r][c].exits(heading);
+            if exits.len() > 1 && !split_cells[r * self.width() + c] {
+                split_cells[r * self.width() + c] = true;
+                splits += 1;
+            }
+            let targets: Vec<Result<Beam, Beam>> = exits
+                .iter()
+                .map(|e| match self.step((r, c), e.towards) {
+                    Some(pos) => Ok((pos, e.heading)),
+                    None => Err(((r, c), e.towards)),
+                })
+                .collect();
+            for next in targets.iter().flatten() {
+                indegree[self.index(*next)] += 1;
+                pending.push(*next);
+            }
+            feeds[self.index(beam)] = Some(targets);
+            reached += 1;
+        }
+
+        // Then, count timelines in topological order:
+        let mut counts = vec![0_u64; indegree.len()];
+        let mut ready = Vec::new();
+        for start in starts {
+            counts[self.index(start)] += 1;
+            if indegree[self.index(start)] == 0 {
+                ready.push(start);
+            }
+        }
+        // Leaving beams are counted by index too, remembering which ones
+        // turned up, in order.
+        let mut exit_counts = vec![0_u64; beams];
+        let mut exits = Vec::new();
+        let mut counted = 0;
+        while let Some(beam) = ready.pop() {
+            counted += 1;
+            let count = counts[self.index(beam)];
+            for target in feeds[self.index(beam)].iter().flatten() {
+                let total = match target {
+                    Ok(next) => &mut counts[self.index(*next)],
+                    Err(leaving) => {
+                        if exit_counts[self.index(*leaving)] == 0 {
+                            exits.push(*leaving);
+                        }
+                        &mut exit_counts[self.index(*leaving)]
+                    }
+                };
+                *total = total
+                    .checked_add(count)
+                    .ok_or(format!("timeline count overflowed at {:?}", beam.0))?;
+                if let Ok(next) = target {
+                    indegree[self.index(*next)] -= 1;
+                    if indegree[self.index(*next)] == 0 {
+                        ready.push(*next);
+                    }
+                }
+            }
+        }
+        if counted < reached {
+            return Err(String::from(
+                "a beam loops back on itself, so timelines are unbounded",
+            ));
+        }
+
+        Ok(Trace {
+            splits,
+            exits: exits
+                .into_iter()
+                .map(|leaving| (leaving, exit_counts[self.index(leaving)]))
+                .collect(),
+        })
+    }
+}
@@ -0,0 +1,78 @@
+// Timing a day with one job against several (--bench), to see whether
+// --jobs is worth it for that day's input. The answers have to come out the
+// same either way, so that gets checked too.
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use crate::answer::Answer;
+use crate::parallel;
+use crate::parse;
+use crate::Solver;
+
+// Timing is how long a batch of runs took, with the answers they gave.
+struct Timing {
+    jobs: usize,
+    median: Duration,
+    fastest: Duration,
+    answers: Vec<Answer>,
+}
+
+fn time(
+    solve: Solver,
+    text: &str,
+    parts: &[parse::Part],
+    params: &parse::Params,
+    runs: usize,
+    jobs: usize,
+) -> Result<Timing, String> {
+    parallel::set_jobs(jobs);
+    let mut answers = Vec::new();
+    let mut times = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let input: parse::Input = Box::new(Cursor::new(text.to_string()));
+        let start = Instant::now();
+        answers = solve(input, parts, params)?;
+        times.push(start.elapsed());
+    }
+    times.sort();
+    Ok(Timing {
+        jobs: parallel::jobs(),
+        median: times[times.len() / 2],
+        fastest: times[0],
+        answers,
+    })
+}
+
+// bench solves the input `runs` times with one job, then `runs` times with
+// `jobs` of them, and prints how the two compare.
+pub(crate) fn bench(
+    solve: Solver,
+    text: &str,
+    parts: &[parse::Part],
+    params: &parse::Params,
+    runs: usize,
+    jobs: usize,
+) -> Result<(), String> {
+    let runs = runs.max(1);
+    let sequential = time(solve, text, parts, params, runs, 1)?;
+    let parallel = time(solve, text, parts, params, runs, jobs)?;
+    if sequential.answers != parallel.answers {
+        return Err(format!(
+            "{} jobs answered {:?}, but 1 job answered {:?}",
+            parallel.jobs, parallel.answers, sequential.answers
+        ));
+    }
+
+    for timing in [&sequential, &parallel] {
+        println!(
+            "{:>3} job{}: median {:>10.3?}, fastest {:>10.3?}, {:.2}x",
+            timing.jobs,
+            if timing.jobs == 1 { " " } else { "s" },
+            timing.median,
+            timing.fastest,
+            sequential.median.as_secs_f64() / timing.median.as_secs_f64(),
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+// Parsing for column-oriented ASCII layouts, like day 6's worksheets: text
+// laid out in fixed-width blocks, separated by columns of nothing but
+// whitespace, with a footer row along the bottom.
+
+use grid::Grid;
+
+// Block is one chunk of a columnar layout: the cells above the footer, and the
+// footer row underneath them.
+#[derive(Debug)]
+pub(crate) struct Block {
+    pub(crate) body: Grid<char>,
+    pub(crate) footer: Vec<char>,
+}
+
+// blocks splits the given lines into Blocks, wherever there's a column that's
+// blank in every line. Lines don't need to be the same length: anything past
+// the end of a line counts as a space. The last line is the footer.
+pub(crate) fn blocks(lines: &[Vec<char>]) -> Vec<Block> {
+    let Some((footer, body)) = lines.split_last() else {
+        return Vec::new();
+    };
+    let cell = |line: &Vec<char>, c: usize| *line.get(c).unwrap_or(&' ');
+    let width = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    let is_blank = |c: usize| lines.iter().all(|l| cell(l, c) == ' ');
+
+    // Find the [start, end) span of every run of non-blank columns:
+    let mut spans = Vec::new();
+    let mut start = None;
+    for c in 0..=width {
+        match (start, c < width && !is_blank(c)) {
+            (None, true) => start = Some(c),
+            (Some(s), false) => {
+                spans.push((s, c));
+                start = None;
+            }
+            _ => (),
+        }
+    }
+
+    spans
+        .into_iter()
+        .map(|(s, e)| {
+            let mut body_grid = Grid::new(body.len(), e - s);
+            for (r, line) in body.iter().enumerate() {
+                for c in s..e {
+                    body_grid[(r, c - s)] = cell(line, c);
+                }
+            }
+            Block {
+                body: body_grid,
+                footer: (s..e).map(|c| cell(footer, c)).collect(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,105 @@
+// The config file, for things that would otherwise need passing on every run:
+// where inputs live, the default year and output format, and params for each
+// day. It's read from --config if given, or else the first of
+// $XDG_CONFIG_HOME/aoc/config.toml (~/.config/aoc/config.toml by default) and
+// aoc.toml in this crate that exists. Anything passed on the command line wins
+// over it. See aoc.toml for what goes in it.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::output;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub(crate) struct Config {
+    pub(crate) year: Option<u32>,
+    pub(crate) format: Option<output::Format>,
+    // Where each day's input is, with {year} and {day} (zero padded) filled in.
+    inputs: Option<String>,
+    // The file the session token is kept in, for fetching inputs.
+    session: Option<String>,
+    // Params for each day, under [params.dayNN].
+    params: HashMap<String, HashMap<String, toml::Value>>,
+    // Relative paths are relative to the config file, wherever it is.
+    #[serde(skip)]
+    dir: PathBuf,
+}
+
+fn home() -> Option<PathBuf> {
+    env::var_os("HOME").map(PathBuf::from)
+}
+
+// candidates is where to look for a config file, in order, when not told.
+fn candidates() -> Vec<PathBuf> {
+    let user = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home().map(|h| h.join(".config")));
+    user.map(|u| u.join("aoc").join("config.toml"))
+        .into_iter()
+        .chain([Path::new(env!("CARGO_MANIFEST_DIR")).join("aoc.toml")])
+        .collect()
+}
+
+impl Config {
+    // load reads the config at the given path, or the first one found if
+    // there isn't one. Having no config at all is fine; there's just nothing
+    // in it.
+    pub(crate) fn load(path: Option<&Path>) -> Result<Config, String> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match candidates().into_iter().find(|p| p.exists()) {
+                Some(path) => path,
+                None => return Ok(Config::default()),
+            },
+        };
+        log::debug!("reading config from {}", path.display());
+
+        let text = fs::read_to_string(&path).map_err(|e| format!("can't read {path:?}: {e}"))?;
+        let mut config: Config = toml::from_str(&text).map_err(|e| format!("{path:?}: {e}"))?;
+        config.dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        Ok(config)
+    }
+
+    // resolve expands a leading ~, and makes relative paths relative to the
+    // config file.
+    fn resolve(&self, path: &str) -> PathBuf {
+        match (path.strip_prefix("~/"), home()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => self.dir.join(path),
+        }
+    }
+
+    pub(crate) fn input(&self, year: u32, day: u32) -> Option<PathBuf> {
+        self.inputs.as_ref().map(|inputs| {
+            self.resolve(
+                &inputs
+                    .replace("{year}", &year.to_string())
+                    .replace("{day}", &format!("{day:02}")),
+            )
+        })
+    }
+
+    pub(crate) fn session(&self) -> Option<PathBuf> {
+        self.session.as_ref().map(|s| self.resolve(s))
+    }
+
+    // params returns the day's params, as key=value, to go ahead of the ones
+    // from the command line (so that those win).
+    pub(crate) fn params(&self, day: u32) -> Vec<String> {
+        let Some(params) = self.params.get(&format!("day{day:02}")) else {
+            return Vec::new();
+        };
+        params
+            .iter()
+            .map(|(k, v)| match v {
+                toml::Value::String(s) => format!("{k}={s}"),
+                v => format!("{k}={v}"),
+            })
+            .collect()
+    }
+}
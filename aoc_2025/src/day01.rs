@@ -1,62 +1,227 @@
-use std::io::{BufReader, BufRead};
-use std::fs::File;
-
+use crate::answer::Answer;
 use crate::parse;
+use crate::parser;
+use crate::solution::{self, Solution};
+use crate::verify;
 
-const DEBUG: bool = false;
-
-fn parse_instruction(text: String) -> Instruction {
-    let (dir, val) = text.split_at(1);
-    Instruction{
-        direction:  match dir {
-            "L" => Ok(Direction::Left),
-            "R" => Ok(Direction::Right),
-            _ => Err(format!("invalid direction {:}", dir))
-        }.unwrap(),
-        value:  val.parse().unwrap(),
-    }
+// instruction parses a line like `L68`: a direction, then how far to turn.
+fn instruction<'a>() -> impl parser::Parser<'a, Instruction> {
+    let direction = parser::alt(
+        parser::value(parser::tag("L"), Direction::Left),
+        parser::value(parser::tag("R"), Direction::Right),
+    );
+    parser::map(
+        parser::pair(direction, parser::int()),
+        |(direction, value)| Instruction{ direction, value },
+    )
 }
 
+#[derive(Debug, Clone)]
 enum Direction {
     Left,
     Right
 }
+
+#[derive(Debug)]
 struct Instruction {
     direction: Direction,
     value: usize
 }
 
-pub(crate) fn solve(input: BufReader<File>, part: parse::Part) {
-    let mut final_count: i32 = 0;
-    input
-        .lines()
-        .map_while(Result::ok)
-        .map(parse_instruction)
-        .fold(50, |acc, instruction| {
-            let next = acc 
-                + (instruction.value as i32 * (
-                    if matches!(instruction.direction, Direction::Left) { -1 } else { 1 }
-                ));
-            match part {
-                parse::Part::Part1 => {
-                    if next % 100 == 0 {
-                        final_count += 1;
-                    }
-                },
-                parse::Part::Part2 => {
-                    if acc != 0 && next <= 0 {
-                        final_count += 1;
-                    }
-                    final_count += next.abs() / 100;
+// Click is the dial pointing at zero, either while passing it partway through
+// an instruction, or on the instruction's last step.
+#[derive(Debug)]
+struct Click {
+    instruction: usize, // index of the instruction that caused it
+}
+
+// Dial is a dial numbered 0 through size - 1, which wraps around.
+#[derive(Debug)]
+struct Dial {
+    size: usize,
+    position: usize,
+}
+
+impl Dial {
+    // turn applies the instruction at the given index, and yields a Click for
+    // every time the dial points at zero along the way. Where the dial stops
+    // is in self.position afterwards: a zero-length turn can leave it at zero
+    // without any clicks.
+    fn turn(&mut self, index: usize, instruction: &Instruction) -> impl ExactSizeIterator<Item = Click> {
+        let (clicks, next) = match instruction.direction {
+            Direction::Right => {
+                let end = self.position + instruction.value;
+                (end / self.size, end % self.size)
+            }
+            Direction::Left => {
+                // Going left, we only pass zero once we've covered the
+                // distance down to it, which is a full turn if we start there:
+                let to_zero = if self.position == 0 { self.size } else { self.position };
+                let clicks = match instruction.value.checked_sub(to_zero) {
+                    None => 0,
+                    Some(rest) => 1 + rest / self.size,
+                };
+                let next = (self.position + self.size - instruction.value % self.size) % self.size;
+                (clicks, next)
+            }
+        };
+        self.position = next;
+        (0..clicks).map(move |_| Click{ instruction: index })
+    }
+}
+
+// Clicks counts all the times the dial points at zero, over every
+// instruction, along with how many instructions left it resting there.
+pub(crate) struct Clicks {
+    clicks: usize,
+    rests: usize,
+}
+
+// Params:
+//   size:  how many numbers are on the dial (default 100).
+//   start: where the dial starts (default 50).
+// Run with -vvv to trace the dial's position and clicks after every
+// instruction.
+impl Solution for Clicks {
+    fn parse(input: parse::Input, params: &parse::Params) -> Result<Clicks, String> {
+        let mut dial = Dial {
+            size: params.get_or("size", 100)?,
+            position: params.get_or("start", 50)?,
+        };
+        if dial.position >= dial.size {
+            return Err(format!(
+                "the dial must start on one of its numbers, 0 to {}, not {}",
+                dial.size.saturating_sub(1), dial.position
+            ))
+        }
+
+        let text = parser::read(input)?;
+        let mut clicks = 0;
+        let mut rests = 0;
+        for (i, instruction) in parser::parse_all(parser::lines(instruction()), &text)?.iter().enumerate() {
+            let turn = dial.turn(i, instruction);
+            log::trace!("{i}: {instruction:?} -> {}", dial.position);
+            clicks += turn.len();
+            if log::log_enabled!(log::Level::Trace) {
+                for click in turn {
+                    log::trace!("\tzero (instruction {})", click.instruction);
                 }
             }
-            let next = next.rem_euclid(100);
-            if DEBUG {
-                println!("line acc: {}", next);
-                println!("final count: {}", final_count);
+            if dial.position == 0 {
+                log::trace!("\tat rest on zero");
+                rests += 1;
             }
-            next
-        });
+        }
+
+        Ok(Clicks { clicks, rests })
+    }
+
+    // Part 1 only counts the dial coming to rest on zero:
+    fn part1(&self, _: &parse::Params) -> Result<Answer, String> {
+        Ok(Answer::from(self.rests))
+    }
+
+    fn part2(&self, _: &parse::Params) -> Result<Answer, String> {
+        Ok(Answer::from(self.clicks))
+    }
+}
 
-    println!("output: {}", final_count)
+// reference turns the dial one number at a time, counting every time it
+// points at zero.
+pub(crate) fn reference(input: parse::Input, part: parse::Part, params: &parse::Params) -> Result<Answer, String> {
+    let size: usize = params.get_or("size", 100)?;
+    let mut position: usize = params.get_or("start", 50)?;
+    let mut count = 0_usize;
+    let text = parser::read(input)?;
+    for instruction in parser::parse_all(parser::lines(instruction()), &text)? {
+        for _ in 0..instruction.value {
+            position = match instruction.direction {
+                Direction::Left => (position + size - 1) % size,
+                Direction::Right => (position + 1) % size,
+            };
+            if position == 0 && matches!(part, parse::Part::Part2) {
+                count += 1;
+            }
+        }
+        if position == 0 && matches!(part, parse::Part::Part1) {
+            count += 1;
+        }
+    }
+    Ok(Answer::from(count))
+}
+
+pub(crate) fn random_input(rng: &mut verify::Rng) -> String {
+    (0..rng.between(1, 20))
+        .map(|_| {
+            let dir = if rng.chance(50) { "L" } else { "R" };
+            format!("{dir}{}", rng.between(0, 350))
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+inventory::submit! {
+    solution::Registration {
+        year: 2025,
+        day: 1,
+        title: "Secret Entrance",
+        parts: parse::PARTS,
+        solve: solution::solve::<Clicks>,
+        reference: Some(verify::Reference {
+            solve: reference,
+            random_input,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn input(text: &str) -> parse::Input {
+        Box::new(Cursor::new(text.to_string()))
+    }
+
+    // solve answers both parts, checking them against the reference on the
+    // way.
+    fn solve(text: &str, params: &[&str]) -> (Answer, Answer) {
+        let params = parse::Params::from(params.iter().map(|p| p.to_string()).collect::<Vec<_>>());
+        let clicks = Clicks::parse(input(text), &params).unwrap();
+        let answers = (clicks.part1(&params).unwrap(), clicks.part2(&params).unwrap());
+        assert_eq!(answers.0, reference(input(text), parse::Part::Part1, &params).unwrap(), "part 1 of {text:?}");
+        assert_eq!(answers.1, reference(input(text), parse::Part::Part2, &params).unwrap(), "part 2 of {text:?}");
+        answers
+    }
+
+    #[test]
+    fn zero_length_turns_on_zero_rest_there() {
+        assert_eq!(solve("L50\nR0\nL0", &[]), (Answer::from(3), Answer::from(1)));
+    }
+
+    #[test]
+    fn full_left_turns_from_zero() {
+        for k in 1..=4 {
+            let text = format!("L50\nL{}", k * 100);
+            assert_eq!(solve(&text, &[]), (Answer::from(2), Answer::from(1 + k)), "{text}");
+        }
+        assert_eq!(solve("L250", &["start=0"]), (Answer::from(0), Answer::from(2)));
+    }
+
+    #[test]
+    fn other_sizes_and_starts() {
+        assert_eq!(solve("R7\nL25\nR5\nL0", &["size=10", "start=3"]), (Answer::from(3), Answer::from(4)));
+        assert_eq!(solve("R1\nR1\nL2", &["size=2", "start=1"]), (Answer::from(1), Answer::from(2)));
+        for start in 0..7 {
+            let start = format!("start={start}");
+            solve("L13\nR6\nR0\nL7\nR21", &["size=7", &start]);
+        }
+    }
+
+    #[test]
+    fn start_off_the_dial_is_an_error() {
+        let params = parse::Params::from(vec![String::from("size=10"), String::from("start=10")]);
+        assert!(Clicks::parse(input("R1"), &params).is_err());
+    }
 }
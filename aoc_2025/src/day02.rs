@@ -1,67 +1,399 @@
-use std::{io::{BufReader, Read}, fs::File};
+use std::collections::BTreeSet;
 
+use crate::answer::Answer;
+use crate::parallel;
 use crate::parse;
+use crate::parser;
+use crate::solution::{self, Solution};
+use crate::stream;
+use crate::verify;
 
-fn check_invalid_one(num: usize) -> bool {
-    let digits = num.checked_ilog10().unwrap() + 1;
-    let divisor = 10_usize.pow(digits / 2);
-    digits.is_multiple_of(2) && num / divisor == num % divisor
-}
-
+// A number made of a repeated block of digits is the block times a "repunit"
+// of 1s spaced out by the block length:
 // a = 123123123
 // 123000000 + 000123000 + 000000123
 // a = 123 * 10^6 + 123 * 10^3 + 123
-fn check_invalid_two(num: usize) -> bool {
-    let digits = num.checked_ilog10().unwrap() + 1;
-    for length in 1..digits / 2 + 1 {
-        if !digits.is_multiple_of(length) {
-            continue
+// a = 123 * (10^6 + 10^3 + 1) = 123 * 1001001
+// So rather than checking every number in a range, we can work out which
+// blocks land in the range with a division, and sum them as a series. The
+// same goes for any base, not just 10.
+
+// Repetition describes which IDs are invalid: ones whose digits (in `base`)
+// are a block repeated between `min_repeats` and `max_repeats` times, with
+// the block length limited to `block_lengths` if it's set.
+#[derive(Debug)]
+struct Repetition {
+    min_repeats: u32,
+    max_repeats: u32,
+    block_lengths: Option<Vec<u32>>,
+    base: u128,
+}
+
+impl Repetition {
+    // from_params reads the day's params (described on Ranges below), with
+    // max_repeats as the part's default.
+    fn from_params(params: &parse::Params, max_repeats: u32) -> Result<Repetition, String> {
+        let block_lengths: String = params.get_or("block_lengths", String::new())?;
+        let block_lengths = match block_lengths.is_empty() {
+            true => None,
+            false => Some(block_lengths
+                .split(",")
+                .map(|l| l
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("can't parse block length {l:?}: {e}")))
+                .collect::<Result<Vec<u32>, String>>()?),
+        };
+        let repetition = Repetition {
+            min_repeats: params.get_or("min_repeats", 2)?,
+            max_repeats: params.get_or("max_repeats", max_repeats)?,
+            block_lengths,
+            base: params.get_or("base", 10)?,
+        };
+        if repetition.base < 2 {
+            return Err(format!("base must be at least 2, not {}", repetition.base))
+        }
+        // A block repeated once is the whole ID, which by_digits never tries,
+        // so min_repeats=1 would quietly behave like 2.
+        if repetition.min_repeats < 2 {
+            return Err(format!("min_repeats must be at least 2, not {}", repetition.min_repeats))
         }
-        let mut nums = vec![];
-        for i in 0..(digits / length) {
-            let small = 10_usize.pow(i * length);
-            nums.push((num / small) % 10_usize.pow(length));
+        if repetition.min_repeats > repetition.max_repeats {
+            return Err(format!(
+                "min_repeats ({}) is above max_repeats ({})",
+                repetition.min_repeats, repetition.max_repeats
+            ))
         }
-        let head = nums.first().unwrap();
-        if nums.iter().all(|num| num == head) {
-            return true
+        Ok(repetition)
+    }
+
+    fn allows(&self, digits: u32, block: u32) -> bool {
+        let repeats = digits / block;
+        repeats >= self.min_repeats
+            && repeats <= self.max_repeats
+            && self.block_lengths.as_ref().is_none_or(|lengths| lengths.contains(&block))
+    }
+
+    fn repunit(&self, digits: u32, block: u32) -> u128 {
+        (self.base.pow(digits) - 1) / (self.base.pow(block) - 1)
+    }
+
+    // blocks_between returns the range of `block`-digit blocks which, repeated
+    // out to `digits` digits, land in [low, high], along with the repunit to
+    // multiply them by.
+    fn blocks_between(&self, low: u128, high: u128, digits: u32, block: u32) -> (u128, u128, u128) {
+        let m = self.repunit(digits, block);
+        let first = self.base.pow(block - 1).max(low.div_ceil(m));
+        let last = (self.base.pow(block) - 1).min(high / m);
+        (first, last, m)
+    }
+
+    // by_digits splits [low, high] up by the number of digits, and for each
+    // gives the block lengths that evenly divide it.
+    fn by_digits(&self, low: u64, high: u64) -> Vec<(u128, u128, u32, Vec<u32>)> {
+        let (low, high) = (low.max(1) as u128, high as u128);
+        if low > high {
+            return Vec::new()
         }
+        (low.ilog(self.base) + 1..=high.ilog(self.base) + 1)
+            .map(|digits| (
+                low.max(self.base.pow(digits - 1)),
+                high.min(self.base.pow(digits) - 1),
+                digits,
+                (1..digits).filter(|b| digits % b == 0).collect(),
+            ))
+            .collect()
     }
-    false
+
+    // sum_between sums the invalid IDs in [low, high].
+    //
+    // An ID can repeat several block lengths at once (111111 is 1, 11 and 111
+    // repeated), so to avoid counting it more than once, it's credited to its
+    // shortest block only. The IDs whose shortest block is b are the ones that
+    // repeat b, minus the ones whose shortest block is a divisor of b; and an
+    // ID with shortest block p is invalid if p divides any allowed block.
+    fn sum_between(&self, low: u64, high: u64) -> u128 {
+        let mut sum = 0;
+        for (low, high, digits, blocks) in self.by_digits(low, high) {
+            let mut shortest: Vec<(u32, u128)> = Vec::new();
+            for b in &blocks {
+                let shorter: u128 = shortest
+                    .iter()
+                    .filter(|(p, _)| b % p == 0)
+                    .map(|(_, s)| s)
+                    .sum();
+                let (first, last, m) = self.blocks_between(low, high, digits, *b);
+                let repeating = if first > last { 0 } else { m * (first + last) * (last - first + 1) / 2 };
+                shortest.push((*b, repeating - shorter));
+            }
+
+            sum += shortest
+                .iter()
+                .filter(|(p, _)| blocks.iter().any(|b| b % p == 0 && self.allows(digits, *b)))
+                .map(|(_, s)| s)
+                .sum::<u128>();
+        }
+        sum
+    }
+
+    // list_between returns every invalid ID in [low, high], in order.
+    fn list_between(&self, low: u64, high: u64) -> Vec<u128> {
+        let mut ids = BTreeSet::new();
+        for (low, high, digits, blocks) in self.by_digits(low, high) {
+            for b in blocks.into_iter().filter(|b| self.allows(digits, *b)) {
+                let (first, last, m) = self.blocks_between(low, high, digits, b);
+                ids.extend((first..=last).map(|block| block * m));
+            }
+        }
+        ids.into_iter().collect()
+    }
+
+    // is_invalid checks a single ID the obvious way: write out its digits,
+    // and try every block length that's allowed.
+    fn is_invalid(&self, id: u64) -> bool {
+        let mut digits = Vec::new();
+        let mut rest = id as u128;
+        while rest > 0 {
+            digits.push(rest % self.base);
+            rest /= self.base;
+        }
+        let len = digits.len() as u32;
+        (1..len)
+            .filter(|b| len.is_multiple_of(*b) && self.allows(len, *b))
+            .any(|b| digits.chunks(b as usize).all(|chunk| chunk == &digits[..b as usize]))
+    }
+}
+
+// range parses one `low-high` range, checking it's the right way round.
+fn range<'a>() -> impl parser::Parser<'a, (u64, u64)> {
+    parser::padded(parser::try_map(parser::range(), |(low, high)| match low <= high {
+        true => Ok((low, high)),
+        false => Err(String::from("low bound is above the high bound")),
+    }))
 }
 
-fn sum_invalid(bounds: (&str, &str), check: fn(i: usize) -> bool) -> usize {
-    let parse_bound = |b: &str| b
-        .trim()
-        .parse::<usize>()
-        .expect("can't parse bound");
-    let low = parse_bound(bounds.0);
-    let high = parse_bound(bounds.1);
-
-    let mut sum = 0;
-    for i in low..high+1 {
-        if check(i) {
-            sum += i;
+// parse_ranges reads a list of `low-high` ranges, separated by commas or
+// newlines. Whitespace around anything is fine, and so are empty entries (like
+// from a trailing comma or newline). Errors say which range is the problem,
+// and where it is in the input. The input is streamed a range at a time,
+// so only the ranges themselves are kept.
+fn parse_ranges(input: parse::Input) -> Result<Vec<(u64, u64)>, String> {
+    let mut ranges = Vec::new();
+    for token in stream::Tokens::new(input, b",\n") {
+        let (pos, token) = token?;
+        if token.trim().is_empty() {
+            continue
         }
-    };
+        let parsed = parser::parse_at(range(), &token, pos)
+            .map_err(|e| format!("range {}: {e}", ranges.len() + 1))?;
+        ranges.push(parsed);
+    }
+    Ok(ranges)
+}
 
-    sum
+// merge_ranges combines any ranges that overlap or touch, so that no ID gets
+// counted twice. The result is sorted.
+fn merge_ranges(mut ranges: Vec<(u64, u64)>) -> Vec<(u64, u64)> {
+    ranges.sort();
+    let mut merged: Vec<(u64, u64)> = Vec::new();
+    for (low, high) in ranges {
+        match merged.last_mut() {
+            Some(last) if low <= last.1.saturating_add(1) => last.1 = last.1.max(high),
+            _ => merged.push((low, high)),
+        }
+    }
+    merged
 }
 
-pub(crate) fn solve(mut input: BufReader<File>, part: parse::Part) {
-    let mut buf = String::new();
-    input.read_to_string(&mut buf).unwrap();
+// Ranges are the merged ranges of IDs to check.
+pub(crate) struct Ranges(Vec<(u64, u64)>);
+
+// Params:
+//   min_repeats:   the fewest times a block must repeat (default 2, which is
+//                  also the lowest it can go).
+//   max_repeats:   the most times a block may repeat (default 2 for part 1,
+//                  unlimited for part 2).
+//   block_lengths: comma separated block lengths to allow (default any).
+//   base:          the base to read IDs' digits in (default 10).
+//   list:          log the invalid IDs in each range, as well as the sum.
+impl Ranges {
+    fn sum_invalid(&self, params: &parse::Params, max_repeats: u32) -> Result<Answer, String> {
+        let repetition = Repetition::from_params(params, max_repeats)?;
+        let list = params.flag("list")?;
 
-    let check = match part {
-        parse::Part::Part1 => check_invalid_one,
-        parse::Part::Part2 => check_invalid_two
-    };
+        // Each range is independent, so they can be summed on --jobs threads;
+        // the logging waits until they're all back, to keep it in order. It's
+        // at warn, since the list was asked for and should show without -v.
+        let sums = parallel::map(&self.0, |(low, high)| (
+            repetition.sum_between(*low, *high),
+            list.then(|| repetition.list_between(*low, *high)),
+        ));
 
-    let sum: usize = buf
-        .split(",")
-        .map(|elem| elem.split_once("-").unwrap())
-        .map(|bounds| sum_invalid(bounds, check))
-        .sum();
+        let sum: Answer = self.0
+            .iter()
+            .zip(sums)
+            .inspect(|((low, high), (_, listed))| if let Some(listed) = listed {
+                log::warn!("{low}-{high}: {listed:?}")
+            })
+            .map(|(_, (sum, _))| Answer::from(sum))
+            .sum();
 
-    println!("output: {}", sum)
+        Ok(sum)
+    }
+}
+
+impl Solution for Ranges {
+    fn parse(input: parse::Input, _: &parse::Params) -> Result<Ranges, String> {
+        Ok(Ranges(merge_ranges(parse_ranges(input)?)))
+    }
+
+    fn part1(&self, params: &parse::Params) -> Result<Answer, String> {
+        self.sum_invalid(params, 2)
+    }
+
+    fn part2(&self, params: &parse::Params) -> Result<Answer, String> {
+        self.sum_invalid(params, u32::MAX)
+    }
+}
+
+// reference checks every ID in every range, one at a time, and sums the ones
+// that are invalid (each just once, even if the ranges overlap).
+pub(crate) fn reference(input: parse::Input, part: parse::Part, params: &parse::Params) -> Result<Answer, String> {
+    let repetition = Repetition::from_params(params, match part {
+        parse::Part::Part1 => 2,
+        parse::Part::Part2 => u32::MAX,
+    })?;
+    let invalid: BTreeSet<u64> = parse_ranges(input)?
+        .into_iter()
+        .flat_map(|(low, high)| low..=high)
+        .filter(|id| repetition.is_invalid(*id))
+        .collect();
+    Ok(invalid.into_iter().map(Answer::from).sum())
+}
+
+// random_input makes a few ranges, of IDs with anywhere up to 8 digits, which
+// sometimes overlap.
+pub(crate) fn random_input(rng: &mut verify::Rng) -> String {
+    (0..rng.between(1, 5))
+        .map(|_| {
+            let digits = rng.between(1, 8) as u32;
+            let low = rng.between(1, 10_u64.pow(digits));
+            format!("{low}-{}", low + rng.between(0, 300))
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+inventory::submit! {
+    solution::Registration {
+        year: 2025,
+        day: 2,
+        title: "Gift Shop",
+        parts: parse::PARTS,
+        solve: solution::solve::<Ranges>,
+        reference: Some(verify::Reference {
+            solve: reference,
+            random_input,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn ranges(text: &str) -> Result<Vec<(u64, u64)>, String> {
+        parse_ranges(Box::new(Cursor::new(text.to_string())))
+    }
+
+    fn params(params: &[&str]) -> parse::Params {
+        parse::Params::from(params.iter().map(|p| p.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn bad_params_are_errors() {
+        let ranges = Ranges(vec![(11, 22)]);
+        for bad in [
+            &["base=ten"][..],
+            &["base=1"],
+            &["min_repeats=-1"],
+            &["min_repeats=1"],
+            &["max_repeats=many"],
+            &["min_repeats=3", "max_repeats=2"],
+            &["block_lengths=1,x"],
+            &["list=maybe"],
+        ] {
+            assert!(ranges.part2(&params(bad)).is_err(), "{bad:?}");
+        }
+        assert_eq!(ranges.part2(&params(&["base=10", "list"])), Ok(Answer::from(33)));
+    }
+
+    // The default params get fuzzed along with every other day's; these are
+    // the rest.
+    #[test]
+    fn sum_between_agrees_with_is_invalid() {
+        let reference = verify::Reference { solve: reference, random_input };
+        for (parts, settings) in [
+            (parse::PARTS, &["base=2"][..]),
+            (parse::PARTS, &["base=3"]),
+            (parse::PARTS, &["base=16", "max_repeats=3"]),
+            (parse::PARTS, &["block_lengths=1,2"]),
+            (parse::PARTS, &["block_lengths=3"]),
+            (&[parse::Part::Part2], &["min_repeats=3"]),
+            (&[parse::Part::Part2], &["min_repeats=3", "max_repeats=4", "base=4"]),
+        ] {
+            verify::fuzz(solution::solve::<Ranges>, &reference, parts, 300, Some(2025), &params(settings))
+                .unwrap_or_else(|e| panic!("{settings:?}: {e}"));
+        }
+    }
+
+    #[test]
+    fn parsing_tolerates_whitespace_and_empty_entries() {
+        let want = vec![(11, 22), (95, 115), (998, 1012)];
+        for text in [
+            "11-22,95-115,998-1012",
+            "11-22,95-115,998-1012,\n",
+            " 11-22 ,\t95-115,\n998-1012 \n\n",
+            "11-22,,95-115\n,998-1012,",
+            "11-22,\r\n95-115,\r\n998-1012\r\n",
+        ] {
+            assert_eq!(ranges(text), Ok(want.clone()), "{text:?}");
+        }
+        assert_eq!(ranges(""), Ok(vec![]));
+    }
+
+    #[test]
+    fn parsing_errors_say_where() {
+        assert_eq!(
+            ranges("11-22,\n95-115,22-11"),
+            Err(String::from("range 3: line 2, column 8: low bound is above the high bound"))
+        );
+        assert_eq!(
+            ranges("11-22,95-115,\n 998+1012"),
+            Err(String::from("range 3: line 2, column 5: expected \"-\", found \"+1012\""))
+        );
+        assert_eq!(
+            ranges("11-22,95-x"),
+            Err(String::from("range 2: line 1, column 10: expected a number, found \"x\""))
+        );
+        assert_eq!(
+            ranges("11-22 33"),
+            Err(String::from("range 1: line 1, column 7: expected end of input, found \"33\""))
+        );
+    }
+
+    #[test]
+    fn overlapping_and_touching_ranges_merge() {
+        assert_eq!(merge_ranges(vec![(5, 10), (1, 3), (8, 20)]), vec![(1, 3), (5, 20)]);
+        assert_eq!(merge_ranges(vec![(1, 3), (4, 6)]), vec![(1, 6)]);
+        assert_eq!(merge_ranges(vec![(1, 3), (5, 6)]), vec![(1, 3), (5, 6)]);
+        assert_eq!(merge_ranges(vec![(2, 9), (3, 4), (2, 9)]), vec![(2, 9)]);
+        assert_eq!(merge_ranges(vec![(7, u64::MAX), (u64::MAX, u64::MAX)]), vec![(7, u64::MAX)]);
+
+        // So IDs in more than one range only count once.
+        let merged = Ranges(merge_ranges(ranges("11-22,15-33,33-33").unwrap()));
+        assert_eq!(merged.part1(&params(&[])), Ok(Answer::from(11 + 22 + 33)));
+    }
 }
@@ -1,84 +1,266 @@
-use std::{io::{BufReader, BufRead}, fs::File, collections::HashMap};
+use std::str::FromStr;
 
+use crate::answer::Answer;
+use crate::parallel;
 use crate::parse;
+use crate::parser;
+use crate::solution::{self, Solution};
+use crate::verify;
 
 #[derive(Debug)]
 struct Bank{
     batteries: Vec<u8>,
-    cache: HashMap<(u32, usize), u64>
 }
 
 impl Bank {
-    fn max_joltage(&self, left: Option<usize>, right: Option<usize>) -> u64 {
-        match (left, right) {
-            (Some(l), Some(r)) => (self.batteries.get(l).unwrap() * 10 + self.batteries.get(r).unwrap()).into(),
-            (Some(l), None) => {
-                (l+1..self.batteries.len())
-                .map(|r| self.max_joltage(left, Some(r)))
-                .max()
-                .unwrap()
-            }
-            _ => {
-            (0..self.batteries.len() - 1)
-                .map(|l| self.max_joltage(Some(l), None))
-                .max()
-                .unwrap()
+    // max_joltage picks the `digits` batteries that make the largest number,
+    // keeping them in order, and returns it along with the indices picked.
+    // Since the length is fixed, the largest number is the lexicographically
+    // largest subsequence, which we can build greedily: keep a stack of picks,
+    // and whenever a bigger digit comes along, pop smaller ones off the top as
+    // long as there are enough batteries left to still fill every digit.
+    fn max_joltage(&self, digits: usize) -> Result<(Answer, Vec<usize>), String> {
+        if digits > self.batteries.len() {
+            return Err(format!(
+                "can't pick {digits} digits from a bank of {}", self.batteries.len()
+            ))
+        }
+
+        let mut can_drop = self.batteries.len() - digits;
+        let mut picked: Vec<usize> = Vec::with_capacity(self.batteries.len());
+        for (i, battery) in self.batteries.iter().enumerate() {
+            while can_drop > 0 && picked.last().is_some_and(|top| self.batteries[*top] < *battery) {
+                picked.pop();
+                can_drop -= 1;
             }
+            picked.push(i);
         }
+        picked.truncate(digits);
+
+        let joltage = picked
+            .iter()
+            .fold(Answer::from(0), |acc, i| acc * Answer::from(10) + Answer::from(self.batteries[*i]));
+        Ok((joltage, picked))
     }
 
-    fn max_joltage_for_digits(&mut self, digits: u32, start_idx: usize) -> u64 {
-        if digits == 0 {
-            return 0
-        }
-        
-        let key = &(digits, start_idx);
-        if self.cache.contains_key(key) {
-            return *self.cache.get(key).unwrap()
+    // render draws the bank with the picked batteries highlighted.
+    fn render(&self, picked: &[usize], style: &Explain) -> String {
+        let mut picked = picked.iter().peekable();
+        self.batteries
+            .iter()
+            .enumerate()
+            .map(|(i, battery)| {
+                if picked.next_if_eq(&&i).is_none() {
+                    return battery.to_string()
+                }
+                match style {
+                    Explain::Ansi => format!("\x1b[1;32m{battery}\x1b[0m"),
+                    _ => format!("[{battery}]"),
+                }
+            })
+            .collect()
+    }
+}
+
+// Explain is how (or whether) to draw each bank's picks as we go.
+#[derive(Debug, PartialEq)]
+enum Explain {
+    Off,
+    Ansi,
+    Brackets,
+}
+
+impl FromStr for Explain {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "false" | "off" => Ok(Explain::Off),
+            "true" | "ansi" => Ok(Explain::Ansi),
+            "brackets" => Ok(Explain::Brackets),
+            _ => Err(format!("invalid explain style {s:?}")),
         }
+    }
+}
+
+// battery parses one battery's joltage rating, a single digit.
+fn battery(c: char) -> Result<u8, String> {
+    c.to_digit(10)
+        .map(|d| d as u8)
+        .ok_or_else(|| format!("invalid battery {c:?}"))
+}
+
+// parse_banks reads a bank of batteries per line.
+fn parse_banks(input: parse::Input) -> Result<Vec<Bank>, String> {
+    let text = parser::read(input)?;
+    Ok(parser::parse_all(parser::grid(battery), &text)?
+        .into_iter()
+        .map(|batteries| Bank { batteries })
+        .collect())
+}
+
+pub(crate) struct Banks(Vec<Bank>);
 
-        let max_joltage = (start_idx..=self.batteries.len() - digits as usize)
-            .map(|i| {
-                let digit: u64 = (*self.batteries.get(i).unwrap()).into();
-                let delta = digit * 10_u64.pow(digits - 1);
-                self.max_joltage_for_digits(
-                    digits - 1,
-                    i + 1
-                ) + delta
+// Params:
+//   digits:  how many batteries to turn on in each bank (default 2 for part 1,
+//            12 for part 2).
+//   explain: draw each bank with its picks highlighted, in "ansi" colour (the
+//            default if no style is given) or "brackets".
+impl Banks {
+    fn total_joltage(&self, params: &parse::Params, digits: usize) -> Result<Answer, String> {
+        let digits = params.get_or("digits", digits)?;
+        let explain = params.get_or("explain", Explain::Off)?;
+
+        // Banks are independent, so pick them on --jobs threads, then add
+        // them up (and explain them) in order. Explaining was asked for, so
+        // it's logged at warn, to show without -v.
+        self.0
+            .iter()
+            .zip(parallel::map(&self.0, |b| b.max_joltage(digits)))
+            .try_fold(Answer::from(0), |sum, (b, picks)| {
+                let (joltage, picked) = picks?;
+                let sum = sum + joltage.clone();
+                match explain {
+                    Explain::Off => log::debug!("joltage: {}", joltage),
+                    _ => log::warn!("{}  joltage: {joltage}  sum: {sum}", b.render(&picked, &explain)),
+                }
+                Ok(sum)
             })
-            .max();
-        let out = max_joltage.unwrap_or(0);
-        self.cache.insert(*key, out);
-        out
     }
 }
 
-impl From<String> for Bank {
-    fn from(value: String) -> Self {
-        Bank {
-            batteries: value
-                .chars()
-                .map(|c| c.to_digit(10).unwrap() as u8)
-                .collect(),
-            cache: HashMap::new()
+impl Solution for Banks {
+    fn parse(input: parse::Input, _: &parse::Params) -> Result<Banks, String> {
+        Ok(Banks(parse_banks(input)?))
+    }
+
+    fn part1(&self, params: &parse::Params) -> Result<Answer, String> {
+        self.total_joltage(params, 2)
+    }
+
+    fn part2(&self, params: &parse::Params) -> Result<Answer, String> {
+        self.total_joltage(params, 12)
+    }
+}
+
+// max_joltage_dp works out the best pick for every number of digits, from
+// the back of the bank forwards: the best k digits from some battery on either
+// skip it, or take it and then the best k - 1 digits from the ones after it.
+// Slower than picking greedily, but hard to get wrong, and still quick enough
+// for real inputs.
+fn max_joltage_dp(batteries: &[u8], digits: usize) -> Option<Answer> {
+    // best[k] is the biggest number k of the batteries seen so far can make,
+    // or None if there aren't k of them yet.
+    let mut best: Vec<Option<Answer>> = vec![None; digits + 1];
+    best[0] = Some(Answer::from(0));
+    for battery in batteries.iter().rev() {
+        for k in (1..=digits).rev() {
+            let Some(rest) = &best[k - 1] else { continue };
+            let place = (1..k).fold(Answer::from(1), |acc, _| acc * Answer::from(10));
+            let taken = Some(Answer::from(*battery) * place + rest.clone());
+            best[k] = best[k].clone().max(taken);
         }
     }
+    best.pop().flatten()
+}
+
+pub(crate) fn reference(input: parse::Input, part: parse::Part, params: &parse::Params) -> Result<Answer, String> {
+    let digits = params.get_or("digits", match part {
+        parse::Part::Part1 => 2,
+        parse::Part::Part2 => 12,
+    })?;
+    parse_banks(input)?
+        .iter()
+        .map(|b| max_joltage_dp(&b.batteries, digits).ok_or(String::from("bank is too small")))
+        .sum()
 }
 
+pub(crate) fn random_input(rng: &mut verify::Rng) -> String {
+    (0..rng.between(1, 4))
+        .map(|_| (0..rng.between(12, 15)).map(|_| rng.between(1, 9).to_string()).collect())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+inventory::submit! {
+    solution::Registration {
+        year: 2025,
+        day: 3,
+        title: "Lobby",
+        parts: parse::PARTS,
+        solve: solution::solve::<Banks>,
+        reference: Some(verify::Reference {
+            solve: reference,
+            random_input,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bank(digits: &str) -> Bank {
+        Bank { batteries: digits.chars().map(|c| battery(c).unwrap()).collect() }
+    }
+
+    // picked_value reads the number back out of the picked batteries.
+    fn picked_value(bank: &Bank, picked: &[usize]) -> Answer {
+        picked
+            .iter()
+            .fold(Answer::from(0), |acc, i| acc * Answer::from(10) + Answer::from(bank.batteries[*i]))
+    }
 
-pub(crate) fn solve(input: BufReader<File>, part: parse::Part) {
-    let sum: u64 = input
-        .lines()
-        .map(|l| l.unwrap())
-        .map(Bank::from)
-        .map(|mut b| {
-            match part {
-                parse::Part::Part1 => b.max_joltage(None, None),
-                parse::Part::Part2 => b.max_joltage_for_digits(12, 0)
+    #[test]
+    fn greedy_matches_the_examples() {
+        for (digits, two, twelve) in [
+            ("987654321111111", 98_u64, 987654321111_u64),
+            ("811111111111119", 89, 811111111119),
+            ("234234234234278", 78, 434234234278),
+            ("818181911112111", 92, 888911112111),
+        ] {
+            let b = bank(digits);
+            assert_eq!(b.max_joltage(2).unwrap().0, Answer::from(two), "{digits}");
+            assert_eq!(b.max_joltage(12).unwrap().0, Answer::from(twelve), "{digits}");
+        }
+    }
+
+    #[test]
+    fn greedy_picks_the_first_of_tied_batteries() {
+        let b = bank("811111111111119");
+        assert_eq!(b.max_joltage(2).unwrap().1, vec![0, 14]);
+        let mut twelve: Vec<usize> = (0..=10).collect();
+        twelve.push(14);
+        assert_eq!(b.max_joltage(12).unwrap().1, twelve);
+    }
+
+    #[test]
+    fn greedy_matches_dp() {
+        let mut rng = verify::Rng::new(3);
+        for _ in 0..300 {
+            let b = bank(&(0..rng.between(12, 16)).map(|_| rng.between(1, 9).to_string()).collect::<String>());
+            for digits in [2, 12] {
+                let (joltage, picked) = b.max_joltage(digits).unwrap();
+                assert_eq!(Some(joltage.clone()), max_joltage_dp(&b.batteries, digits), "{b:?}, {digits} digits");
+                assert_eq!(picked.len(), digits, "{b:?}, {digits} digits");
+                assert!(picked.windows(2).all(|w| w[0] < w[1]), "{b:?} picked {picked:?} out of order");
+                assert_eq!(picked_value(&b, &picked), joltage, "{b:?}, {digits} digits");
             }
-        })
-        .inspect(|j| println!("joltage: {}", j))
-        .sum();
+        }
+    }
+
+    #[test]
+    fn too_many_digits_is_an_error() {
+        assert!(bank("12345").max_joltage(6).is_err());
+        assert_eq!(max_joltage_dp(&bank("12345").batteries, 6), None);
+    }
 
-    println!("\noutput: {}", sum)
-}
\ No newline at end of file
+    #[test]
+    fn bad_params_are_errors() {
+        let banks = Banks(vec![bank("987654321111111")]);
+        for param in ["digits=abc", "digits=-1", "explain=bold"] {
+            let params = parse::Params::from(vec![param.to_string()]);
+            assert!(banks.part1(&params).is_err(), "{param}");
+        }
+    }
+}
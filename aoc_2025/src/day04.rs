@@ -1,9 +1,10 @@
-use std::{io::{BufReader, BufRead}, fs::File};
-
+use crate::answer::Answer;
 use crate::parse;
+use crate::parser;
+use crate::solution::{self, Solution};
 
-#[derive(Debug)]
-struct Grid {
+#[derive(Debug, Clone)]
+pub(crate) struct Grid {
     elems: Vec<Vec<bool>>
 }
 
@@ -33,7 +34,7 @@ impl Grid {
                     }
                 })
                 .filter(|(x, y)| self.roll_at((*x, *y)))
-                // .inspect(|c| println!("\tcoord: {c:?}"))
+                .inspect(|c| log::trace!("\tcoord: {c:?}"))
                 .count()
             )
             .sum()
@@ -49,9 +50,9 @@ impl Grid {
                     .iter()
                     .enumerate()
                     .filter(|(_, is_roll)| **is_roll)
-                    // .inspect(|(y, _)| println!("counting for {:?}", (x, y)))
+                    .inspect(|(y, _)| log::trace!("counting for {:?}", (x, y)))
                     .filter(|(y, _)| self.count_adjacent((x, *y)) < max_adjacent)
-                    // .inspect(|e| println!("take {e:?}"))
+                    .inspect(|e| log::trace!("take {e:?}"))
                     .map(|(y, _)| (x, y))
                     .collect::<Vec<(usize, usize)>>()
             })
@@ -66,40 +67,52 @@ impl Grid {
     }
 }
 
-impl From<BufReader<File>> for Grid {
-    fn from(value: BufReader<File>) -> Self {
-        Grid {
-            elems: value.lines()
-            .map(|line| line.unwrap())
-            .map(|line| line
-                .chars()
-                .map(|c| c == '@')
-                .collect()
-            )
-            .collect()
-        }
+// roll parses one spot on the floor: a roll of paper, or nothing.
+fn roll(c: char) -> Result<bool, String> {
+    match c {
+        '@' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(format!("invalid floor tile {c:?}")),
     }
 }
 
-pub(crate) fn solve(input: BufReader<File>, part: parse::Part) {
-    let mut grid: Grid = input.into();
-    // println!("grid: {grid:?}");
-    let output: usize = match part {
-        parse::Part::Part1 => grid.get_accessible(4).len(),
-        parse::Part::Part2 => {
-            let mut sum = 0_usize;
-            loop {
-                let coords = grid.get_accessible(4);
-                if coords.is_empty() {
-                    break;
-                }
-                sum += coords.len();
-                grid = grid.remove_rolls(coords);
+impl Solution for Grid {
+    fn parse(input: parse::Input, _: &parse::Params) -> Result<Grid, String> {
+        let text = parser::read(input)?;
+        let grid = Grid { elems: parser::parse_all(parser::grid(roll), &text)? };
+        log::trace!("grid: {grid:?}");
+        Ok(grid)
+    }
+
+    fn part1(&self, _: &parse::Params) -> Result<Answer, String> {
+        Ok(Answer::from(self.get_accessible(4).len()))
+    }
+
+    // Part 2 keeps removing rolls until there are none left to take, so it
+    // works on its own copy of the grid.
+    fn part2(&self, _: &parse::Params) -> Result<Answer, String> {
+        let mut grid = self.clone();
+        let mut sum = 0_usize;
+        loop {
+            let coords = grid.get_accessible(4);
+            if coords.is_empty() {
+                break;
             }
-            sum
+            sum += coords.len();
+            grid = grid.remove_rolls(coords);
         }
-    };
+        Ok(Answer::from(sum))
+    }
+}
+ 
 
-    println!("output: {}", output);
+inventory::submit! {
+    solution::Registration {
+        year: 2025,
+        day: 4,
+        title: "Printing Department",
+        parts: parse::PARTS,
+        solve: solution::solve::<Grid>,
+        reference: None,
+    }
 }
- 
\ No newline at end of file
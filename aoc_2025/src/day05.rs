@@ -1,146 +1,150 @@
-use std::{io::{BufReader, BufRead}, fs::File};
+use std::collections::BTreeSet;
 
+use crate::answer::Answer;
 use crate::parse;
+use crate::parser;
+use crate::solution::{self, Solution};
+use crate::stream;
+use crate::verify;
 
 type Bound = (i64, i64);
 
-#[derive(Debug)]
-struct Node {
-  value: Bound,
-  left: Option<Box<Node>>,
-  right: Option<Box<Node>>,
-  is_lower: bool,
-}
-
-impl Node {
-  fn get_cmp_val(&self) -> i64 {
-    if self.is_lower { self.value.0 } else { self.value.1 }
-  }
-
-  fn find(&self, v: i64) -> bool {
-    if self.value.0 <= v && self.value.1 >= v {
-      return true
-    } 
-    let next = {
-      let cmp_value = self.get_cmp_val();
-      if cmp_value < v { &self.right } else { &self.left }
-    };
-    match next {
-      None => false,
-      Some(node) => node.find(v)
+// merge_bounds sorts the bounds and merges any that overlap, so that every
+// ID is in at most one of them.
+fn merge_bounds(mut sorted: Vec<Bound>) -> Vec<Bound> {
+  sorted.sort_by_key(|b| b.0);
+
+  let mut finalized_bounds = Vec::new();
+  let mut finalized_bound = (0, 0);
+  for (idx, bound) in sorted.iter().enumerate() {
+    if idx == 0 {
+      finalized_bound = *bound;
+      continue
     }
-  }
-
-  fn add(&mut self, bound: Bound, lower: bool) {
-    let b_val = if self.is_lower { bound.0 } else { bound.1 };
-    let cmp_val = self.get_cmp_val();
-    if cmp_val > b_val {
-      match &mut self.left {
-        None => self.left = Some(Box::from(Node::from_bound(bound, lower))),
-        Some(b) => b.add(bound, lower),
-      }
-    } else if cmp_val < b_val {
-      match &mut self.right {
-        None => self.right = Some(Box::from(Node::from_bound(bound, lower))),
-        Some(b) => b.add(bound, lower),
-      }
+    if bound.0 <= finalized_bound.1 {
+      finalized_bound.1 = if bound.1 > finalized_bound.1 { bound.1 } else { finalized_bound.1 }
+    } else {
+      log::debug!("pushing {finalized_bound:?}");
+      finalized_bounds.push(finalized_bound);
+      finalized_bound = *bound
     }
   }
+  finalized_bounds.push(finalized_bound);
+  finalized_bounds
+}
 
-  fn from_bound(bound: Bound, lower: bool) -> Node {
-    Node { value: bound, left: None, right: None, is_lower: lower }
-  }
+// find checks whether v is in any of the merged bounds. Since they're sorted
+// and don't overlap, the only candidate is the last one starting at or
+// before v.
+fn find(merged: &[Bound], v: i64) -> bool {
+  let idx = merged.partition_point(|b| b.0 <= v);
+  idx > 0 && merged[idx - 1].1 >= v
 }
 
-#[derive(Debug)]
-struct Tree {
-  lower_root: Option<Node>,
-  upper_root: Option<Node>
+// Inventory is the merged ranges of fresh IDs, and the IDs to check.
+pub(crate) struct Inventory {
+  merged: Vec<Bound>,
+  ids: Vec<i64>,
 }
 
-impl Tree {
-  fn new() -> Tree {
-    Tree { lower_root: None, upper_root: None }
-  }
+impl Solution for Inventory {
+  fn parse(input: parse::Input, _: &parse::Params) -> Result<Inventory, String> {
+    // The ranges come first, then a blank line, then the IDs. Stream them a
+    // line at a time, rather than holding the whole input at once.
+    let mut sections = stream::Sections::new(input);
 
-  fn add(&mut self, bound: Bound) {
-    match &mut self.lower_root {
-      Some(n) => n.add(bound, true),
-      None => self.lower_root = Some(Node::from_bound(bound, true))
-    }
-    match &mut self.upper_root {
-      Some(n) => n.add(bound, false),
-      None => self.upper_root = Some(Node::from_bound(bound, false))
+    let mut bounds = Vec::new();
+    for line in sections.section().ok_or("no ranges in input")? {
+      let (pos, line) = line?;
+      bounds.push(parser::parse_at(parser::range(), &line, pos)?);
     }
-  }
+    log::trace!("{} bounds", bounds.len());
+    let merged = merge_bounds(bounds);
 
-  fn find(&self, v: i64) -> bool {
-    (match &self.lower_root {
-      None => false,
-      Some(n) => n.find(v)
-    }) || match &self.upper_root {
-      None => false,
-      Some(n) => n.find(v)
+    let mut ids = Vec::new();
+    for line in sections.section().ok_or("no IDs after the ranges")? {
+      let (pos, line) = line?;
+      ids.push(parser::parse_at(parser::int(), &line, pos)?);
+    }
+    // Blank lines at the end are fine, but anything after them would be
+    // silently dropped, so it's an error.
+    while let Some(mut section) = sections.section() {
+      if let Some(line) = section.next() {
+        let (pos, line) = line?;
+        return Err(format!("{pos}: expected end of input, found {line:?}"));
+      }
     }
-  }
-}
-
 
+    Ok(Inventory { merged, ids })
+  }
 
-pub(crate) fn solve(input: BufReader<File>, part: parse::Part) {
-  let lines = input
-    .lines()
-    .map(|l| l.unwrap())
-    .fold(String::new(), |acc, l| acc + l.as_str() + "\n");
+  fn part1(&self, _: &parse::Params) -> Result<Answer, String> {
+    let fresh = self.ids.iter()
+      .filter(|id| find(&self.merged, **id))
+      .inspect(|id| log::debug!("{id} is fresh"))
+      .count();
 
-  let (bounds, ids) = lines.split_once("\n\n").unwrap();
-  // println!("bounds {bounds}, ids {ids}");
+    Ok(Answer::from(fresh))
+  }
 
-  if matches!(part, parse::Part::Part1) {
-    let mut tree = Tree::new();
-    for bound in bounds.lines() {
-      let (low, high) = bound.split_once("-").unwrap();
-      tree.add((low.parse().unwrap(), high.parse().unwrap()))
-    }
+  fn part2(&self, _: &parse::Params) -> Result<Answer, String> {
+    let sum: i64 = self.merged.iter()
+      .map(|(low, high)| high - low + 1)
+      .inspect(|v| log::debug!("bound has {v} ids"))
+      .sum();
 
-    let fresh = ids.lines()
-      .map(|id| id.parse().unwrap())
-      .filter(|id| tree.find(*id))
-      .inspect(|id| println!("{id} is fresh"))
-      .count();
+    Ok(Answer::from(sum))
+  }
+}
 
-    println!("output: {fresh}");
-  } else {
-    let mut sorted = bounds
-      .lines()
-      .map(|b| b.split_once("-").unwrap())
-      .map(|(low, high)| (low.parse().unwrap(), high.parse().unwrap()))
-      .collect::<Vec<Bound>>();
-    sorted.sort_by_key(|b| b.0);
-
-    let mut finalized_bounds = Vec::new();
-    let mut finalized_bound = (0, 0);
-    for (idx, bound) in sorted.iter().enumerate() {
-      if idx == 0 {
-        finalized_bound = *bound;
-        continue
-      }
-      if bound.0 <= finalized_bound.1 {
-        finalized_bound.1 = if bound.1 > finalized_bound.1 { bound.1 } else { finalized_bound.1 }
-      } else {
-        println!("pushing {finalized_bound:?}");
-        finalized_bounds.push(finalized_bound);
-        finalized_bound = *bound
-      }
+// reference solves the day without merging anything: part 1 checks each ID
+// against every range, and part 2 cuts the number line up at every range's
+// ends, counting the pieces that any range covers.
+pub(crate) fn reference(input: parse::Input, part: parse::Part, _: &parse::Params) -> Result<Answer, String> {
+  let text = parser::read(input)?;
+  let inventory = parser::sections(parser::lines(parser::range()), parser::lines(parser::int::<i64>()));
+  let (bounds, ids): (Vec<Bound>, Vec<i64>) = parser::parse_all(inventory, &text)?;
+
+  match part {
+    parse::Part::Part1 => Ok(Answer::from(ids
+      .into_iter()
+      .filter(|id| bounds.iter().any(|(low, high)| low <= id && id <= high))
+      .count())),
+    parse::Part::Part2 => {
+      let cuts: BTreeSet<i64> = bounds.iter().flat_map(|(low, high)| [*low, high + 1]).collect();
+      let cuts: Vec<i64> = cuts.into_iter().collect();
+      Ok(Answer::from(cuts
+        .windows(2)
+        .filter(|w| bounds.iter().any(|(low, high)| *low <= w[0] && w[0] <= *high))
+        .map(|w| w[1] - w[0])
+        .sum::<i64>()))
     }
-    finalized_bounds.push(finalized_bound);
+  }
+}
 
-    let sum: i64 = finalized_bounds.iter()
-      .map(|(low, high)| high - low + 1)
-      .inspect(|v| println!("bound has {v} ids"))
-      .sum();
+pub(crate) fn random_input(rng: &mut verify::Rng) -> String {
+  let mut out = String::new();
+  for _ in 0..rng.between(1, 6) {
+    let low = rng.between(1, 40);
+    out += &format!("{low}-{}\n", low + rng.between(0, 15));
+  }
+  out += "\n";
+  for _ in 0..rng.between(1, 10) {
+    out += &format!("{}\n", rng.between(1, 60));
+  }
+  out
+}
 
-    println!("output: {sum}");
+inventory::submit! {
+  solution::Registration {
+    year: 2025,
+    day: 5,
+    title: "Cafeteria",
+    parts: parse::PARTS,
+    solve: solution::solve::<Inventory>,
+    reference: Some(verify::Reference {
+      solve: reference,
+      random_input,
+    }),
   }
-  
-}
\ No newline at end of file
+}
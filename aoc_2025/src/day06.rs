@@ -1,132 +1,107 @@
 use grid::Grid;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
 
+use crate::answer::Answer;
+use crate::columns;
+use crate::expr::Operator;
 use crate::parse;
+use crate::parser;
+use crate::solution::{self, Solution};
 
-#[derive(Debug)]
-enum Operation {
-    Add,
-    Mult,
-}
-
-impl From<&char> for Operation {
-    fn from(value: &char) -> Self {
-        match value {
-            '+' => Operation::Add,
-            '*' => Operation::Mult,
-            _ => panic!("invalid operator string"),
-        }
+// number_from reads a number out of a problem's row or column, ignoring the
+// spaces used to line things up.
+fn number_from(chars: impl Iterator<Item = char>) -> Result<Answer, String> {
+    let digits: String = chars.filter(|c| *c != ' ').collect();
+    if digits.is_empty() {
+        return Err(String::from("problem has a blank row or column"));
     }
+    digits
+        .parse()
+        .map_err(|e| format!("can't parse number {digits:?}: {e:?}"))
 }
 
 #[derive(Debug)]
 struct Problem {
     elements: Grid<char>,
-    operation: Operation,
+    operation: Operator,
 }
 
 impl Problem {
-    fn get_horizontal_numbers(&self) -> Vec<i64> {
-        let mut out = Vec::new();
-        for col in self.elements.iter_cols() {
-            let num_str: String = col.filter(|c| **c != ' ').collect();
-            out.push(num_str.parse().unwrap());
-        }
-        out
+    fn get_horizontal_numbers(&self) -> Result<Vec<Answer>, String> {
+        self.elements
+            .iter_rows()
+            .map(|row| number_from(row.copied()))
+            .collect()
     }
 
-    fn get_vertical_numbers(&self) -> Vec<i64> {
-        let mut out = Vec::new();
-        for row in self.elements.iter_rows() {
-            let num_str: String = row.filter(|c| **c != ' ').collect();
-            out.push(num_str.parse().unwrap());
-        }
-        out
+    // get_vertical_numbers reads the numbers a column at a time, the way
+    // cephalopods write them: right to left, so the rightmost column is the
+    // first operand.
+    fn get_vertical_numbers(&self) -> Result<Vec<Answer>, String> {
+        let mut numbers = self
+            .elements
+            .iter_cols()
+            .map(|col| number_from(col.copied()))
+            .collect::<Result<Vec<Answer>, String>>()?;
+        numbers.reverse();
+        Ok(numbers)
     }
 
     // solve computes the answer to the Problem, given the provided rules for
     // part 1 vs 2.
-    fn solve(self, part: &parse::Part) -> i64 {
-        let init = match self.operation {
-            Operation::Add => 0,
-            Operation::Mult => 1,
-        };
+    fn solve(&self, part: &parse::Part) -> Result<Answer, String> {
         let elements = match part {
-            parse::Part::Part1 => self.get_horizontal_numbers(),
-            parse::Part::Part2 => self.get_vertical_numbers(),
+            parse::Part::Part1 => self.get_horizontal_numbers()?,
+            parse::Part::Part2 => self.get_vertical_numbers()?,
         };
-        elements.iter().fold(init, |acc, e| match self.operation {
-            Operation::Add => acc + e,
-            Operation::Mult => acc * e,
-        })
+        self.operation.fold(&elements)
     }
 }
 
-// problems_from consumes a Grid of arranged input characters and produces a
-// list of Problems that can be solved using the rules of cephalopod math, and
-// either part 1 or 2 notation.
-fn problems_from(arranged_input: Grid<char>) -> Vec<Problem> {
-    let col_len = arranged_input.rows() - 1; // don't count the operator row
-    let mut problems: Vec<Problem> = Vec::new(); // push problems here
-    let mut current_grid: Vec<char> = Vec::new(); // current sub-Grid
-    let mut last_seen_operation: Option<Operation> = None; // current Operation
+// problems_from turns the blocks of a worksheet into Problems that can be
+// solved using the rules of cephalopod math, and either part 1 or 2 notation.
+// The operator sits at the start of each block's footer.
+fn problems_from(blocks: Vec<columns::Block>) -> Result<Vec<Problem>, String> {
+    blocks
+        .into_iter()
+        .map(|block| {
+            Ok(Problem {
+                operation: Operator::try_from(*block.footer.first().unwrap())?,
+                elements: block.body,
+            })
+        })
+        .collect()
+}
 
-    // Generally, the idea here is to go through each column of the input, and
-    // split the Grid into new sub-Grids delimited by each column of whitespace.
-    // It's a little convoluted:
-    for col in arranged_input.iter_cols() {
-        // If this is unset, we need to get the operation from the bottom row:
-        if last_seen_operation.is_none() {
-            last_seen_operation = Some(col.clone().last().unwrap().into());
-        }
-        // If we got a column of all whitespace, it's a problem boundary: send
-        // along the current subgrid (via clone, I'm tired) and Operation:
-        if col.clone().into_iter().all(|c| *c == ' ') {
-            problems.push(Problem {
-                elements: Grid::from_vec(current_grid.clone(), col_len),
-                operation: last_seen_operation.unwrap(),
-            });
-            // Reset the mutable tracking variables &c:
-            current_grid.clear();
-            last_seen_operation = None;
-            continue;
-        }
-        current_grid.extend(col.take(col_len));
-    }
-    // Push the final problem, if we've still got one (operation is Some):
-    if last_seen_operation.is_some() {
-        problems.push(Problem {
-            elements: Grid::from_vec(current_grid, col_len),
-            operation: last_seen_operation.unwrap(),
-        });
+pub(crate) struct Worksheet(Vec<Problem>);
+
+impl Solution for Worksheet {
+    fn parse(input: parse::Input, _: &parse::Params) -> Result<Worksheet, String> {
+        // The worksheet is laid out in columns, so split it into blocks of
+        // characters separated by blank columns, then parse out problems.
+        // Unlike most days, this can't be streamed a line at a time: every
+        // problem's numbers run down through every line, so the blocks aren't
+        // known until the last line (the operators) is in:
+        let text = parser::read(input)?;
+        let lines = parser::parse_all(parser::grid(Ok::<char, String>), &text)?;
+        Ok(Worksheet(problems_from(columns::blocks(&lines))?))
     }
-    problems
-}
 
-pub(crate) fn solve(input: BufReader<File>, part: parse::Part) {
-    // We're going to wrangle everything into a nice padded Grid, through the
-    // following steps.
-    // First: vectorize the input lines by character:
-    let mut vec_input = input
-        .lines()
-        .map(|l| l.unwrap())
-        .map(|l| l.chars().collect())
-        .collect::<Vec<Vec<char>>>();
-    // Next, using the maximum line length, pad out each line so that we can
-    // rely on a fixed length (this is why it's mutable)
-    let line_len = vec_input.iter().map(|l| l.len()).max().unwrap();
-    for l in vec_input.iter_mut() {
-        l.resize(line_len, ' ');
+    fn part1(&self, _: &parse::Params) -> Result<Answer, String> {
+        self.0.iter().map(|p| p.solve(&parse::Part::Part1)).sum()
     }
-    // Finally, we're going to flatten everything and feed it into a Grid, using
-    // that max line length as the row length.
-    let flattened_input = vec_input.into_iter().flatten().collect();
-    let arranged_input = Grid::from_vec(flattened_input, line_len);
 
-    // Now, we can parse out problems from that grid, and solve them:
-    let problems = problems_from(arranged_input);
-    let out: i64 = problems.into_iter().map(|p| p.solve(&part)).sum();
+    fn part2(&self, _: &parse::Params) -> Result<Answer, String> {
+        self.0.iter().map(|p| p.solve(&parse::Part::Part2)).sum()
+    }
+}
 
-    println!("output: {out:?}")
+inventory::submit! {
+    solution::Registration {
+        year: 2025,
+        day: 6,
+        title: "Trash Compactor",
+        parts: parse::PARTS,
+        solve: solution::solve::<Worksheet>,
+        reference: None,
+    }
 }
@@ -1,131 +1,300 @@
+use crate::answer::Answer;
+use crate::beam::{self, Cell, Exit, Heading};
 use crate::parse;
+use crate::solution::{self, Solution};
+use crate::verify;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufRead, BufReader};
-
-#[derive(Debug)]
-struct Manifold {
-    t: usize, // number of times the manifold was stepped
-    rows: Vec<Vec<char>>,
-    num_splits: usize, // count of observed beam splits at time t
+
+// Tile is everything that can show up in a manifold. The puzzle itself only
+// uses the start, empty space and splitters, but the rest make it easy to try
+// out variants of it.
+#[derive(Debug, PartialEq)]
+enum Tile {
+    Start,
+    Empty,
+    Splitter,      // shifts a falling beam both left and right
+    Mirror,        // '/'
+    ReverseMirror, // '\'
+    Absorber,      // swallows the beam entirely
+    Spreader,      // sends a beam forward and out to both sides
 }
 
-impl Manifold {
-    fn step(&mut self) {
-        if self.is_done() {
-            return;
-        };
-        // based on the constraints, to compute state for the next t, we look at
-        // the current t row and update the row t+1 based on its surroundings:
-        let prev_row = self.rows.get(self.t).unwrap().clone();
-        let next_row = self.rows.get_mut(self.t + 1).unwrap();
-        for i in 0..next_row.len() {
-            let c = next_row[i];
-            match c {
-                '.' => {
-                    if prev_row[i] == '|' || prev_row[i] == 'S' {
-                        next_row[i] = '|'
-                    }
-                }
-                '^' => {
-                    if prev_row[i] == '|' {
-                        self.num_splits += 1;
-                        if i > 0 {
-                            next_row[i - 1] = '|';
-                        }
-                        if i < next_row.len() - 1 {
-                            next_row[i + 1] = '|';
-                        }
-                    }
-                }
-                _ => continue,
-            };
+impl Cell for Tile {
+    fn parse(c: char) -> Result<Tile, String> {
+        match c {
+            'S' => Ok(Tile::Start),
+            '.' => Ok(Tile::Empty),
+            '^' => Ok(Tile::Splitter),
+            '/' => Ok(Tile::Mirror),
+            '\\' => Ok(Tile::ReverseMirror),
+            '#' => Ok(Tile::Absorber),
+            '+' => Ok(Tile::Spreader),
+            _ => Err(format!("invalid manifold tile {c:?}")),
         }
-
-        self.t += 1;
     }
 
-    fn is_done(&self) -> bool {
-        self.t >= self.rows.len() - 1
+    fn start(&self) -> Option<Heading> {
+        match self {
+            Tile::Start => Some(Heading::Down),
+            _ => None,
+        }
     }
 
-    fn print(&self) {
-        let t = self.t;
-        println!("Manifold at t={t}");
-        for row in &self.rows {
-            let repr: String = row.iter().collect();
-            println!("{repr}")
+    fn exits(&self, heading: Heading) -> Vec<Exit> {
+        match self {
+            Tile::Start | Tile::Empty => vec![Exit::straight(heading)],
+            Tile::Splitter if heading.is_vertical() => vec![
+                Exit {
+                    towards: Heading::Left,
+                    heading,
+                },
+                Exit {
+                    towards: Heading::Right,
+                    heading,
+                },
+            ],
+            Tile::Splitter => vec![Exit::straight(heading)],
+            Tile::Mirror if heading.is_vertical() => vec![Exit::straight(heading.turn_right())],
+            Tile::Mirror => vec![Exit::straight(heading.turn_left())],
+            Tile::ReverseMirror if heading.is_vertical() => {
+                vec![Exit::straight(heading.turn_left())]
+            }
+            Tile::ReverseMirror => vec![Exit::straight(heading.turn_right())],
+            Tile::Absorber => vec![],
+            Tile::Spreader => vec![
+                Exit::straight(heading.turn_left()),
+                Exit::straight(heading),
+                Exit::straight(heading.turn_right()),
+            ],
         }
-        let splits = self.num_splits;
-        println!("Total observed splits: {splits}\n")
     }
+}
 
-    fn count_timelines(&self) -> u64 {
-        let mut downstream_cache: HashMap<(usize, usize), u64> = HashMap::new();
-        let start_col = self.rows[0]
-            .iter()
-            .enumerate()
-            .find(|(_, c)| **c == 'S')
-            .unwrap()
-            .0;
-        self._count_timelines((0, start_col), &mut downstream_cache)
+type Manifold = beam::Layout<Tile>;
+
+// count_timelines_memoized is the original recursive approach to part 2. It
+// only understands starts and splitters.
+fn count_timelines_memoized(manifold: &Manifold) -> u64 {
+    let mut downstream_cache: HashMap<(usize, usize), u64> = HashMap::new();
+    let start_col = manifold.rows[0]
+        .iter()
+        .position(|t| *t == Tile::Start)
+        .unwrap();
+    _count_timelines(manifold, (0, start_col), &mut downstream_cache)
+}
+
+fn _count_timelines(
+    manifold: &Manifold,
+    pos: (usize, usize),
+    downstream_cache: &mut HashMap<(usize, usize), u64>,
+) -> u64 {
+    if downstream_cache.contains_key(&pos) {
+        return *downstream_cache.get(&pos).unwrap();
     }
 
-    fn _count_timelines(
-        &self,
-        pos: (usize, usize),
-        downstream_cache: &mut HashMap<(usize, usize), u64>,
-    ) -> u64 {
-        if downstream_cache.contains_key(&pos) {
-            return *downstream_cache.get(&pos).unwrap();
+    // If we're on the last row, we've reached the end of a timeline:
+    if pos.0 >= manifold.rows.len() {
+        return 1;
+    }
+
+    // Otherwise, sum downstream timelines based on the current tile:
+    let downstream_timelines = match manifold.rows[pos.0][pos.1] {
+        Tile::Splitter => {
+            let mut sum = 0;
+            if pos.1 > 0 {
+                sum += _count_timelines(manifold, (pos.0, pos.1 - 1), downstream_cache);
+            }
+            if pos.1 < manifold.rows[pos.0].len() - 1 {
+                sum += _count_timelines(manifold, (pos.0, pos.1 + 1), downstream_cache);
+            }
+            sum
         }
+        _ => _count_timelines(manifold, (pos.0 + 1, pos.1), downstream_cache),
+    };
+    downstream_cache.insert(pos, downstream_timelines);
+    downstream_timelines
+}
 
-        // If we're on the last row, we've reached the end of a timeline:
-        if pos.0 >= self.rows.len() {
-            return 1;
+// Beams sums up a trace of the manifold: how many times the beam split, and
+// how many timelines made it out of the bottom.
+pub(crate) struct Beams {
+    splits: usize,
+    timelines: u64,
+}
+
+impl Solution for Beams {
+    fn parse(input: parse::Input, _: &parse::Params) -> Result<Beams, String> {
+        let manifold = Manifold::parse(input)?;
+
+        // Both parts come out of the same pass. Only timelines that fall out
+        // of the bottom count; ones knocked out the sides are lost.
+        let trace = manifold.trace()?;
+        let mut exits = vec![0_u64; manifold.width()];
+        for ((_, c), count) in trace.leaving(Heading::Down) {
+            exits[c] = count;
         }
+        let timelines = exits
+            .iter()
+            .try_fold(0_u64, |acc, c| acc.checked_add(*c))
+            .ok_or("timeline count overflowed summing exits")?;
+        log::debug!("splits: {}", trace.splits);
+        log::debug!("timelines: {timelines}");
+        log::debug!("timelines per exit column: {exits:?}");
 
-        // Otherwise, sum downstream timelines based on the current char:
-        let downstream_timelines = match self.rows[pos.0][pos.1] {
-            '^' => {
-                let mut sum = 0;
-                if pos.1 > 0 {
-                    sum += self._count_timelines((pos.0, pos.1 - 1), downstream_cache);
-                }
-                if pos.1 < self.rows[pos.0].len() - 1 {
-                    sum += self._count_timelines((pos.0, pos.1 + 1), downstream_cache);
-                }
-                sum
-            }
-            _ => self._count_timelines((pos.0 + 1, pos.1), downstream_cache),
-        };
-        downstream_cache.insert(pos, downstream_timelines);
-        downstream_timelines
+        Ok(Beams {
+            splits: trace.splits,
+            timelines,
+        })
+    }
+
+    fn part1(&self, _: &parse::Params) -> Result<Answer, String> {
+        Ok(Answer::from(self.splits))
+    }
+
+    fn part2(&self, _: &parse::Params) -> Result<Answer, String> {
+        Ok(Answer::from(self.timelines))
     }
 }
 
-pub(crate) fn solve(input: BufReader<File>, part: parse::Part) {
-    let mut manifold = Manifold {
-        t: 0,
-        rows: input
-            .lines()
-            .map(|l| l.unwrap())
-            .filter(|l| !l.is_empty())
-            .map(|l| l.chars().collect())
-            .collect(),
-        num_splits: 0,
-    };
+// count_splits_stepped is the original part 1 approach: step the beams down
+// one row at a time, and count splitters with a beam directly above them. It
+// only understands starts and splitters.
+fn count_splits_stepped(manifold: &Manifold) -> usize {
+    let mut beams: Vec<bool> = manifold.rows[0].iter().map(|t| *t == Tile::Start).collect();
+    let mut splits = 0;
+    for row in &manifold.rows[1..] {
+        let mut next = vec![false; beams.len()];
+        for (i, tile) in row.iter().enumerate().filter(|(i, _)| beams[*i]) {
+            if *tile != Tile::Splitter {
+                next[i] = true;
+                continue;
+            }
+            splits += 1;
+            if i > 0 {
+                next[i - 1] = true;
+            }
+            if i < next.len() - 1 {
+                next[i + 1] = true;
+            }
+        }
+        beams = next;
+    }
+    splits
+}
 
+// reference solves the day the way it was first solved, with the stepping
+// simulation for part 1 and the memoized recursion for part 2.
+pub(crate) fn reference(
+    input: parse::Input,
+    part: parse::Part,
+    _: &parse::Params,
+) -> Result<Answer, String> {
+    let manifold = Manifold::parse(input)?;
     match part {
-        parse::Part::Part1 => {
-            while !manifold.is_done() {
-                manifold.step();
-                manifold.print();
+        parse::Part::Part1 => Ok(Answer::from(count_splits_stepped(&manifold))),
+        parse::Part::Part2 => Ok(Answer::from(count_timelines_memoized(&manifold))),
+    }
+}
+
+// random_input makes a manifold with a start along the top, and splitters
+// scattered on every other row. Splitters are never side by side, since the
+// reference can't cope with that.
+pub(crate) fn random_input(rng: &mut verify::Rng) -> String {
+    let width = rng.between(3, 15) as usize;
+    let start = rng.between(0, width as u64 - 1) as usize;
+    let mut rows = vec![(0..width)
+        .map(|c| if c == start { 'S' } else { '.' })
+        .collect()];
+    for r in 1..rng.between(2, 16) {
+        let mut row = vec!['.'; width];
+        for c in 0..width {
+            if r % 2 == 0 && (c == 0 || row[c - 1] != '^') && rng.chance(30) {
+                row[c] = '^';
             }
         }
-        parse::Part::Part2 => {
-            let sum = manifold.count_timelines();
-            println!("output: {sum}");
+        rows.push(row.into_iter().collect::<String>());
+    }
+    rows.join("\n")
+}
+
+inventory::submit! {
+    solution::Registration {
+        year: 2025,
+        day: 7,
+        title: "Laboratories",
+        parts: parse::PARTS,
+        solve: solution::solve::<Beams>,
+        reference: Some(verify::Reference {
+            solve: reference,
+            random_input,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+.......S.......
+...............
+.......^.......
+...............
+......^.^......
+...............
+.....^.^.^.....
+...............
+....^.^...^....
+...............
+...^.^...^.^...
+...............
+..^...^.....^..
+...............
+.^.^.^.^.^...^.
+...............
+";
+
+    fn manifold(text: &str) -> Manifold {
+        Manifold::parse(Box::new(Cursor::new(text.to_string()))).unwrap()
+    }
+
+    // traced_timelines counts the timelines leaving the bottom, like Beams.
+    fn traced_timelines(manifold: &Manifold) -> u64 {
+        manifold
+            .trace()
+            .unwrap()
+            .leaving(Heading::Down)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    #[test]
+    fn trace_matches_memoized_on_example() {
+        let manifold = manifold(EXAMPLE);
+        assert_eq!(traced_timelines(&manifold), 40);
+        assert_eq!(count_timelines_memoized(&manifold), 40);
+        assert_eq!(manifold.trace().unwrap().splits, 21);
+        assert_eq!(count_splits_stepped(&manifold), 21);
+    }
+
+    #[test]
+    fn trace_matches_memoized_on_generated_manifolds() {
+        let mut rng = verify::Rng::new(2025);
+        for _ in 0..200 {
+            let text = random_input(&mut rng);
+            let manifold = manifold(&text);
+            assert_eq!(
+                traced_timelines(&manifold),
+                count_timelines_memoized(&manifold),
+                "timelines for manifold:\n{text}"
+            );
+            assert_eq!(
+                manifold.trace().unwrap().splits,
+                count_splits_stepped(&manifold),
+                "splits for manifold:\n{text}"
+            );
         }
     }
 }
@@ -1,9 +1,10 @@
+use crate::answer::Answer;
 use crate::parse;
+use crate::parser;
+use crate::solution::{self, Solution};
 use itertools::Itertools;
 use std::collections::HashMap;
-use std::fs::File;
 use std::hash::Hash;
-use std::io::{BufRead, BufReader};
 
 type Point = (i32, i32, i32);
 
@@ -46,22 +47,22 @@ impl<T: Eq + Hash + Clone + Copy> UnionFind<T> {
         let p2 = self.find(&k2).cloned();
         // If neither in a set, use k1 as parent for both:
         if p1.is_none() && p2.is_none() {
-            self.map.insert(k1.clone(), k1.clone());
+            self.map.insert(k1, k1);
             self.map.insert(k2, k1);
             return;
         }
         // If one is not in a set, set its parent to be the other's root:
         if p1.is_none() {
-            self.map.insert(k1, p2.unwrap().clone());
+            self.map.insert(k1, p2.unwrap());
             return;
         }
         if p2.is_none() {
-            self.map.insert(k2, p1.unwrap().clone());
+            self.map.insert(k2, p1.unwrap());
             return;
         }
         // Otherwise, default to uniting under p1
-        let found_p1 = p1.unwrap().clone();
-        let found_p2 = p2.unwrap().clone();
+        let found_p1 = p1.unwrap();
+        let found_p2 = p2.unwrap();
         if found_p1 == found_p2 {
             return;
         }
@@ -85,68 +86,94 @@ impl<T: Eq + Hash + Clone + Copy> UnionFind<T> {
             };
         }
 
-        roots_to_set.values().map(|v| *v).collect()
+        roots_to_set.values().copied().collect()
     }
 }
 
-pub(crate) fn solve(input: BufReader<File>, part: parse::Part) {
-    let mut uf: UnionFind<Point> = UnionFind::new();
-
-    let points = input
-        .lines()
-        .map(|l| l.unwrap())
-        .map(|l| {
-            let elems: Vec<&str> = l.split(",").collect();
-            (
-                elems[0].parse().unwrap(),
-                elems[1].parse().unwrap(),
-                elems[2].parse().unwrap(),
-            )
-        })
-        .collect::<Vec<Point>>();
-
-    let dist_to_points: Vec<(f64, (&Point, &Point))> = points
-        .iter()
-        .combinations(2)
-        .map(|v| {
-            let p1 = v[0];
-            let p2 = v[1];
-            let d = dist(p1, p2);
-            (d, (p1, p2))
+// point parses a junction box's position, as `x,y,z`.
+fn point<'a>() -> impl parser::Parser<'a, Point> {
+    let coords = parser::separated(parser::int(), parser::tag(","));
+    parser::try_map(coords, |c| match c[..] {
+        [x, y, z] => Ok((x, y, z)),
+        _ => Err(format!("expected 3 coordinates, got {}", c.len())),
+    })
+}
+
+// Playground is the junction boxes, along with every pair of them sorted
+// closest first, which is the expensive bit both parts share.
+pub(crate) struct Playground {
+    points: Vec<Point>,
+    dist_to_points: Vec<(f64, (Point, Point))>,
+}
+
+// Params:
+//   connections: how many of the closest pairs to connect for part 1 (default
+//                1000, but 10 for the example).
+impl Solution for Playground {
+    fn parse(input: parse::Input, _: &parse::Params) -> Result<Playground, String> {
+        let text = parser::read(input)?;
+        let points = parser::parse_all(parser::lines(point()), &text)?;
+
+        let dist_to_points = points
+            .iter()
+            .combinations(2)
+            .map(|v| {
+                let p1 = *v[0];
+                let p2 = *v[1];
+                let d = dist(&p1, &p2);
+                (d, (p1, p2))
+            })
+            .filter(|(d, _)| *d != 0.0) // remove self edges
+            .sorted_by(|(d1, _), (d2, _)| d1.total_cmp(d2))
+            .collect();
+
+        Ok(Playground {
+            points,
+            dist_to_points,
         })
-        .filter(|(d, _)| *d != 0.0) // remove self edges
-        .sorted_by(|(d1, _), (d2, _)| d1.total_cmp(d2))
-        .collect();
-
-    match part {
-        parse::Part::Part1 => {
-            // Note: for the example case, we take 10.
-            for (_, (p1, p2)) in dist_to_points.iter().take(1000) {
-                // println!("union {p1:?} and {p2:?}...");
-                uf.union(**p1, **p2);
-            }
-            // Count the number of sets:
-            let prod = uf
-                .count_sets()
-                .iter()
-                .sorted_by_key(|size| **size as i32 * -1)
-                .take(3)
-                .fold(1_u64, |acc, size| acc * *size as u64);
-            println!("output: {prod}")
+    }
+
+    fn part1(&self, params: &parse::Params) -> Result<Answer, String> {
+        let mut uf: UnionFind<Point> = UnionFind::new();
+        let connections = params.get_or("connections", 1000)?;
+        for (_, (p1, p2)) in self.dist_to_points.iter().take(connections) {
+            log::trace!("union {p1:?} and {p2:?}...");
+            uf.union(*p1, *p2);
         }
-        parse::Part::Part2 => {
-            // Let's keep unioning until we get a single set.
-            // Hardly the most clever solution, but it will work...
-            for (_, (p1, p2)) in dist_to_points.iter() {
-                uf.union(**p1, **p2);
-                let set_lens = uf.count_sets();
-                if set_lens.len() == 1 && set_lens[0] == points.len() {
-                    let prod = p1.0 as i64 * p2.0 as i64;
-                    println!("output: {prod}");
-                    return;
-                }
+        // Count the number of sets:
+        let prod = uf
+            .count_sets()
+            .iter()
+            .sorted_by_key(|size| -(**size as i64))
+            .take(3)
+            .map(|size| Answer::from(*size))
+            .product::<Answer>();
+        Ok(prod)
+    }
+
+    fn part2(&self, _: &parse::Params) -> Result<Answer, String> {
+        let mut uf: UnionFind<Point> = UnionFind::new();
+        // Let's keep unioning until we get a single set.
+        // Hardly the most clever solution, but it will work...
+        for (_, (p1, p2)) in self.dist_to_points.iter() {
+            uf.union(*p1, *p2);
+            let set_lens = uf.count_sets();
+            if set_lens.len() == 1 && set_lens[0] == self.points.len() {
+                let prod = Answer::from(p1.0) * Answer::from(p2.0);
+                return Ok(prod);
             }
-            panic!("we've failed to connect everything, somehow.")
         }
+        Err(String::from("we've failed to connect everything, somehow."))
+    }
+}
+
+inventory::submit! {
+    solution::Registration {
+        year: 2025,
+        day: 8,
+        title: "Playground",
+        parts: parse::PARTS,
+        solve: solution::solve::<Playground>,
+        reference: None,
     }
 }
@@ -0,0 +1,146 @@
+// Pulling examples out of a saved puzzle page, to use as fixtures, without
+// going online. Each part's description is an <article class="day-desc">. Its
+// example is the first <pre><code> block, and its answer is (by AoC's
+// convention) the last bit of emphasized code, like <code><em>357</em></code>.
+// Part 2 usually reuses part 1's example, and the blocks in its description
+// tend to be worked diagrams rather than a new input, so its answer goes with
+// part 1's example unless asked otherwise.
+
+use std::fs;
+use std::path::Path;
+
+use scraper::{Html, Selector};
+
+use crate::fixtures;
+use crate::parse;
+
+#[derive(Debug)]
+struct Example {
+    part: parse::Part,
+    input: Option<String>,
+    answer: Option<String>,
+}
+
+fn selector(s: &str) -> Selector {
+    Selector::parse(s).unwrap()
+}
+
+// extract finds each part's example and answer on the page. A page saved
+// before solving part 1 only has part 1.
+fn extract(html: &str) -> Vec<Example> {
+    let page = Html::parse_document(html);
+    let (block, answer) = (selector("pre > code"), selector("code em, em code"));
+    page.select(&selector("article.day-desc"))
+        .zip(parse::PARTS)
+        .map(|(article, part)| Example {
+            part: *part,
+            input: article
+                .select(&block)
+                .next()
+                .map(|code| code.text().collect()),
+            answer: article
+                .select(&answer)
+                .last()
+                .map(|code| code.text().collect::<String>().trim().to_string()),
+        })
+        .collect()
+}
+
+// write_input writes a fixture's input, which is fine if it's already there,
+// as long as it's the same.
+fn write_input(path: &Path, input: &str) -> Result<(), String> {
+    if path.exists() {
+        let existing = fs::read_to_string(path).map_err(|e| format!("can't read {path:?}: {e}"))?;
+        return match existing == input {
+            true => Ok(()),
+            false => Err(format!("{path:?} already exists, with a different input")),
+        };
+    }
+    fs::write(path, input).map_err(|e| format!("can't write {path:?}: {e}"))?;
+    println!("wrote {}", path.display());
+    Ok(())
+}
+
+// examples extracts the examples from the saved page, and writes them as
+// fixtures for the day: "example" for part 1's, which part 2 also gets checked
+// against. With part2_example, part 2's first block is used as its own
+// example instead, as "example-2" if it's different.
+pub(crate) fn examples(
+    year: u32,
+    day: u32,
+    page: &Path,
+    part2_example: bool,
+) -> Result<(), String> {
+    let html = fs::read_to_string(page).map_err(|e| format!("can't read {page:?}: {e}"))?;
+    let examples = extract(&html);
+    if examples.is_empty() {
+        return Err(format!("no puzzle descriptions found in {page:?}"));
+    }
+
+    let dir = fixtures::dir(year, day);
+    fs::create_dir_all(&dir).map_err(|e| format!("can't create {dir:?}: {e}"))?;
+    let mut first: Option<&str> = None;
+    for example in &examples {
+        let own = match example.part {
+            parse::Part::Part1 => true,
+            parse::Part::Part2 => part2_example,
+        };
+        let input = match (example.input.as_deref().filter(|_| own), first) {
+            (Some(input), _) => input,
+            (None, Some(first)) => first,
+            (None, None) => return Err(format!("no example for part {}", example.part)),
+        };
+        let name = match first {
+            Some(first) if first != input => "example-2",
+            _ => "example",
+        };
+        first = first.or(Some(input));
+
+        write_input(&dir.join(format!("{name}.txt")), input)?;
+        match &example.answer {
+            Some(answer) => {
+                let path = dir.join(format!("{name}.answers"));
+                fixtures::write_answers(&path, &[(example.part, answer.clone())])?;
+                println!("part {}: {answer} (in {})", example.part, path.display());
+            }
+            None => log::warn!("no answer found for part {}", example.part),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = include_str!("../fixtures/pages/day03.html");
+
+    #[test]
+    fn extract_finds_each_parts_example_and_answer() {
+        let examples = extract(PAGE);
+        assert_eq!(examples.len(), 2);
+
+        assert_eq!(examples[0].part, parse::Part::Part1);
+        assert_eq!(
+            examples[0].input.as_deref(),
+            Some("987654321111111\n811111111111119\n234234234234278\n818181911112111\n")
+        );
+        // The last emphasized code is the answer, either way round, and
+        // emphasis that isn't code (or outside the article) doesn't count.
+        assert_eq!(examples[0].answer.as_deref(), Some("357"));
+
+        assert_eq!(examples[1].part, parse::Part::Part2);
+        assert_eq!(examples[1].input.as_deref(), Some("987654321111111\n"));
+        assert_eq!(examples[1].answer.as_deref(), Some("3121910778619"));
+    }
+
+    #[test]
+    fn extract_before_part_two() {
+        let part1 = &PAGE[..PAGE.find("<article class=\"day-desc\"><h2 id=\"part2\">").unwrap()];
+        let examples = extract(part1);
+        assert_eq!(examples.len(), 1);
+        assert_eq!(examples[0].answer.as_deref(), Some("357"));
+
+        assert!(extract("<html><body><p>nothing here</p></body></html>").is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+// Operators for folding a list of numbers down to a single answer, the way
+// day 6's cephalopod math works. Everything is checked: results that outgrow
+// an i128 become big Answers, and anything undefined (dividing by zero &c)
+// gives an error instead of garbage.
+
+use crate::answer::Answer;
+
+// MAX_POW_BITS is the biggest power worth working out, at about 300,000
+// digits.
+const MAX_POW_BITS: u64 = 1 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Operator {
+    Add,
+    Sub,
+    Mult,
+    Div,
+    Rem,
+    Pow,
+    Min,
+    Max,
+    Concat, // 12 | 34 = 1234
+}
+
+impl TryFrom<char> for Operator {
+    type Error = String;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '+' => Ok(Operator::Add),
+            '-' => Ok(Operator::Sub),
+            '*' => Ok(Operator::Mult),
+            '/' => Ok(Operator::Div),
+            '%' => Ok(Operator::Rem),
+            '^' => Ok(Operator::Pow),
+            '<' => Ok(Operator::Min),
+            '>' => Ok(Operator::Max),
+            '|' => Ok(Operator::Concat),
+            _ => Err(format!("invalid operator {value:?}")),
+        }
+    }
+}
+
+impl Operator {
+    // identity is the value folding over no operands gives. It's also a right
+    // identity (a op identity == a) for every operator that has one. % doesn't,
+    // and neither does concatenation, since there's no empty number (12 | 0 is
+    // 120). Min and max only have one within the i128 range.
+    pub(crate) fn identity(&self) -> Option<Answer> {
+        match self {
+            Operator::Add | Operator::Sub => Some(Answer::from(0)),
+            Operator::Mult | Operator::Div | Operator::Pow => Some(Answer::from(1)),
+            Operator::Min => Some(Answer::from(i128::MAX)),
+            Operator::Max => Some(Answer::from(i128::MIN)),
+            Operator::Rem | Operator::Concat => None,
+        }
+    }
+
+    pub(crate) fn apply(&self, a: &Answer, b: &Answer) -> Result<Answer, String> {
+        match self {
+            Operator::Add => Ok(a.combine(b, i128::checked_add, |x, y| x + y)),
+            Operator::Sub => Ok(a.combine(b, i128::checked_sub, |x, y| x - y)),
+            Operator::Mult => Ok(a.combine(b, i128::checked_mul, |x, y| x * y)),
+            Operator::Div | Operator::Rem if b.is_zero() => {
+                Err(format!("{a} {self:?} {b} divides by zero"))
+            }
+            Operator::Div => Ok(a.combine(b, i128::checked_div, |x, y| x / y)),
+            Operator::Rem => Ok(a.combine(b, i128::checked_rem, |x, y| x % y)),
+            Operator::Pow => {
+                let e = b
+                    .small()
+                    .ok()
+                    .and_then(|e| u32::try_from(e).ok())
+                    .ok_or(format!("{a} {self:?} {b} needs a small, positive exponent"))?;
+                // Anything but 0, 1 or -1 at least doubles with each power, so
+                // a big exponent would take forever (or all the memory) to
+                // work out.
+                if a.bits() > 1 && u64::from(e).saturating_mul(a.bits() - 1) > MAX_POW_BITS {
+                    return Err(format!("{a} {self:?} {b} is too big to work out"));
+                }
+                Ok(a.combine(b, |x, _| x.checked_pow(e), |x, _| x.pow(e)))
+            }
+            Operator::Min => Ok(a.clone().min(b.clone())),
+            Operator::Max => Ok(a.clone().max(b.clone())),
+            Operator::Concat if b.is_negative() => Err(format!("can't concatenate {a} and {b}")),
+            Operator::Concat => format!("{a}{b}").parse(),
+        }
+    }
+
+    // fold applies the operator left to right across the operands, starting
+    // from the first one (so 10 - 2 - 3 is 5, not -15).
+    pub(crate) fn fold(&self, operands: &[Answer]) -> Result<Answer, String> {
+        match operands.split_first() {
+            None => self
+                .identity()
+                .ok_or(format!("{self:?} of no operands is undefined")),
+            Some((first, rest)) => rest
+                .iter()
+                .try_fold(first.clone(), |acc, e| self.apply(&acc, e)),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+// Fixtures are example inputs with known answers, for checking days against.
+// They live in fixtures/dayNN/ (or fixtures/ in a 2024 day's crate), as
+// NAME.txt for the input, and NAME.answers for the answers, one "part: answer"
+// line per part. Examples don't always cover both parts, so either can be left
+// out. Examples that need params to match the puzzle's (like connecting 10
+// pairs rather than 1000) can have them in NAME.params, one key=value a line.
+
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use crate::parse;
+use crate::solution;
+
+pub(crate) struct Fixture {
+    pub(crate) name: String,
+    pub(crate) input: String,
+    pub(crate) answers: Vec<(parse::Part, String)>,
+    pub(crate) params: Vec<String>,
+}
+
+pub(crate) fn dir(year: u32, day: u32) -> PathBuf {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    match year {
+        2024 => crate_dir
+            .parent()
+            .unwrap()
+            .join("aoc_2024")
+            .join(format!("{day:02}"))
+            .join("rust")
+            .join("fixtures"),
+        _ => crate_dir.join("fixtures").join(format!("day{day:02}")),
+    }
+}
+
+fn parse_answers(text: &str) -> Result<Vec<(parse::Part, String)>, String> {
+    text.lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| {
+            let (part, answer) = l
+                .split_once(':')
+                .ok_or_else(|| format!("expected \"part: answer\", got {l:?}"))?;
+            let part = match part.trim() {
+                "1" => parse::Part::Part1,
+                "2" => parse::Part::Part2,
+                p => return Err(format!("invalid part {p:?}")),
+            };
+            Ok((part, answer.trim().to_string()))
+        })
+        .collect()
+}
+
+// write_answers sets the answers for the given parts in NAME.answers, keeping
+// any others already there.
+pub(crate) fn write_answers(path: &Path, answers: &[(parse::Part, String)]) -> Result<(), String> {
+    let mut all = match path.exists() {
+        true => parse_answers(
+            &fs::read_to_string(path).map_err(|e| format!("can't read {path:?}: {e}"))?,
+        )
+        .map_err(|e| format!("{path:?}: {e}"))?,
+        false => Vec::new(),
+    };
+    all.retain(|(part, _)| !answers.iter().any(|(p, _)| p == part));
+    all.extend(answers.iter().cloned());
+    all.sort_by_key(|(part, _)| part.to_string());
+
+    let text: String = all
+        .iter()
+        .map(|(part, answer)| format!("{part}: {answer}\n"))
+        .collect();
+    fs::write(path, text).map_err(|e| format!("can't write {path:?}: {e}"))
+}
+
+// load reads every fixture for the day, in name order. A day without a
+// fixtures directory just has none.
+pub(crate) fn load(year: u32, day: u32) -> Result<Vec<Fixture>, String> {
+    let dir = dir(year, day);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+        .map_err(|e| format!("can't read {dir:?}: {e}"))?
+        .map(|entry| entry.map(|e| e.path()).map_err(|e| e.to_string()))
+        .collect::<Result<_, _>>()?;
+    paths.retain(|p| p.extension().is_some_and(|e| e == "txt"));
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| {
+            let read =
+                |p: &Path| fs::read_to_string(p).map_err(|e| format!("can't read {p:?}: {e}"));
+            let answers_path = path.with_extension("answers");
+            let answers = match answers_path.exists() {
+                true => parse_answers(&read(&answers_path)?)
+                    .map_err(|e| format!("{answers_path:?}: {e}"))?,
+                false => Vec::new(),
+            };
+            let params_path = path.with_extension("params");
+            let params = match params_path.exists() {
+                true => read(&params_path)?
+                    .lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(String::from)
+                    .collect(),
+                false => Vec::new(),
+            };
+            Ok(Fixture {
+                name: path.file_stem().unwrap().to_string_lossy().into_owned(),
+                input: read(&path)?,
+                answers,
+                params,
+            })
+        })
+        .collect()
+}
+
+// check runs the day's solver on each of its fixtures, and errors on the
+// first answer that doesn't match. Otherwise, it returns how many answers
+// were checked.
+pub(crate) fn check(year: u32, day: u32) -> Result<usize, String> {
+    let registration = solution::find(year, day)
+        .ok_or_else(|| format!("no solver registered for {year} day {day}"))?;
+    let mut checked = 0;
+    for fixture in load(year, day)? {
+        let parts: Vec<parse::Part> = fixture.answers.iter().map(|(p, _)| *p).collect();
+        let input: parse::Input = Box::new(Cursor::new(fixture.input.clone()));
+        let params = parse::Params::from(fixture.params.clone());
+        let answers = (registration.solve)(input, &parts, &params)
+            .map_err(|e| format!("day {day}, {}: {e}", fixture.name))?;
+        for ((part, expected), answer) in fixture.answers.iter().zip(answers) {
+            if answer.to_string() != *expected {
+                return Err(format!(
+                    "day {day}, {}, part {part}: got {answer}, expected {expected}",
+                    fixture.name
+                ));
+            }
+            checked += 1;
+        }
+    }
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_days_match_their_fixtures() {
+        for registration in solution::registered() {
+            check(registration.year, registration.day).unwrap();
+        }
+    }
+}
@@ -0,0 +1,52 @@
+// A small logger, so that diagnostics go to stderr (and only when asked for),
+// leaving stdout for answers. Each day logs under its own target (its module
+// name, like "day05"), which can be used to only show logs from some days.
+//
+// Levels are used like so:
+//   warn:  things going wrong, and whatever a param explicitly asked to see
+//          (like day 3's explain), which is all that's shown by default.
+//   info:  a bit more about a run than that.
+//   debug: summaries and per-record chatter, like each line's answer.
+//   trace: step by step detail.
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+struct Logger {
+    targets: Vec<String>,
+}
+
+// target strips the crate name off a module path target, so that logs from
+// aoc2025::day05 are under "day05".
+fn target<'a>(metadata: &Metadata<'a>) -> &'a str {
+    let target = metadata.target();
+    target.split_once("::").map_or(target, |(_, t)| t)
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+            && (self.targets.is_empty() || self.targets.iter().any(|t| t == target(metadata)))
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("[{}] {}", target(record.metadata()), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+// init sets up logging at warn level, raised by each -v and dropped to just
+// errors by -q. If any targets are given, only they're logged.
+pub(crate) fn init(verbose: u8, quiet: bool, targets: Vec<String>) {
+    let level = match (quiet, verbose) {
+        (true, _) => LevelFilter::Error,
+        (false, 0) => LevelFilter::Warn,
+        (false, 1) => LevelFilter::Info,
+        (false, 2) => LevelFilter::Debug,
+        (false, _) => LevelFilter::Trace,
+    };
+    log::set_boxed_logger(Box::new(Logger { targets })).unwrap();
+    log::set_max_level(level);
+}
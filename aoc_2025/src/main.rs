@@ -1,7 +1,30 @@
-use clap::Parser;
-use std::{collections::HashMap, fs::File, io::BufReader};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand};
+use std::{
+    env,
+    io::{self, Read},
+    path::PathBuf,
+    process,
+    time::Instant,
+};
 
+mod answer;
+mod beam;
+mod bench;
+mod columns;
+mod config;
+mod examples;
+mod expr;
+mod fixtures;
+mod logging;
+mod output;
+mod parallel;
 mod parse;
+mod parser;
+mod scaffold;
+mod solution;
+mod stream;
+mod verify;
+mod watch;
 
 mod day01;
 mod day02;
@@ -12,46 +35,325 @@ mod day06;
 mod day07;
 mod day08;
 
-type Solver = fn(BufReader<File>, parse::Part);
+type Solver =
+    fn(parse::Input, &[parse::Part], &parse::Params) -> Result<Vec<answer::Answer>, String>;
 
 #[derive(Parser)]
-#[command(about)]
+#[command(
+    about,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    // The day of the problem to solve.
+    #[command(subcommand)]
+    command: Option<Command>,
+    // The year of the problem to solve (2025, unless the config says
+    // otherwise).
+    #[arg(long, value_parser = clap::value_parser!(u32).range(2015..))]
+    year: Option<u32>,
+    // The day of the problem to solve: 1 to 25, or 1 to 12 from 2025 on.
+    #[arg(long, required_unless_present = "list", value_parser = clap::value_parser!(u32).range(1..=25))]
+    day: Option<u32>,
+    // The problem input, if not where the config says inputs are.
     #[arg(long)]
-    day: String,
-    // The problem input.
+    input_path: Option<PathBuf>,
+    // The part (1 or 2) of the problem to solve. Both, if not given.
+    #[arg(long, value_enum)]
+    part: Option<parse::Part>,
+    // Extra per-day parameters, as key=value (or just key, for flags).
+    #[arg(long = "param")]
+    params: Vec<String>,
+    // Check the answer against the day's reference solver.
     #[arg(long)]
-    input_path: String,
-    // The part (1 or 2) of the problem to solve.
+    verify: bool,
+    // Instead of solving the input, check the solver against the reference
+    // on this many random inputs.
     #[arg(long)]
-    part: String,
+    fuzz: Option<usize>,
+    // Seed for --fuzz, to reproduce a run.
+    #[arg(long)]
+    seed: Option<u64>,
+    // Log more (-v for info, -vv for debug, -vvv for trace).
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+    // Only log errors.
+    #[arg(short, long, conflicts_with = "verbose")]
+    quiet: bool,
+    // Only log from these targets (like "day05").
+    #[arg(long = "log")]
+    log_targets: Vec<String>,
+    // How to print the answer: with banners (plain, the default), as JSON,
+    // or just the answer (quiet).
+    #[arg(long, value_enum)]
+    format: Option<output::Format>,
+    // The config file to use, instead of looking for one.
+    #[arg(long)]
+    config: Option<PathBuf>,
+    // List every registered solver, instead of solving anything.
+    #[arg(long)]
+    list: bool,
+    // Check the day against its fixtures, instead of solving an input.
+    #[arg(long, conflicts_with_all = ["verify", "fuzz"])]
+    examples: bool,
+    // Check the examples and solve the input again whenever the source, the
+    // fixtures or the input change.
+    #[arg(long, conflicts_with_all = ["format", "verify", "fuzz", "examples"])]
+    watch: bool,
+    // Split days' per-line work across this many threads (0 for one per
+    // core). Just the one, if not given.
+    #[arg(long)]
+    jobs: Option<usize>,
+    // Instead of printing the answers, time this many runs with one job
+    // against as many runs with --jobs (one per core, if not given).
+    #[arg(long, conflicts_with_all = ["verify", "fuzz", "examples", "watch"])]
+    bench: Option<usize>,
 }
 
-fn main() {
-    println!("📅 AoC 2025!");
-
-    let solutions: HashMap<String, Solver> = HashMap::from([
-        (String::from("1"), day01::solve as Solver),
-        (String::from("2"), day02::solve as Solver),
-        (String::from("3"), day03::solve as Solver),
-        (String::from("4"), day04::solve as Solver),
-        (String::from("5"), day05::solve as Solver),
-        (String::from("6"), day06::solve as Solver),
-        (String::from("7"), day07::solve as Solver),
-        (String::from("8"), day08::solve as Solver),
-    ]);
+#[derive(Subcommand)]
+enum Command {
+    // Start a new day from a template, with a fixtures directory for its
+    // examples.
+    Scaffold {
+        #[arg(long, default_value_t = 2025, value_parser = clap::value_parser!(u32).range(2024..=2025))]
+        year: u32,
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+        // The puzzle's title, for --list.
+        #[arg(long, default_value = "TODO")]
+        title: String,
+    },
+    // Pull the examples out of a saved puzzle page, into the day's fixtures.
+    Examples {
+        #[arg(long, default_value_t = 2025, value_parser = clap::value_parser!(u32).range(2024..=2025))]
+        year: u32,
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: u32,
+        // The puzzle's page, saved from the browser.
+        page: PathBuf,
+        // Use the first block in part 2's description as its example, rather
+        // than part 1's, for days where part 2 has an example of its own.
+        #[arg(long)]
+        part2_example: bool,
+    },
+    // Print a completion script for the given shell.
+    Completions {
+        shell: clap_complete::Shell,
+    },
+}
+
+// days_in is how many days a year's event has: 25, until 2025 cut it to 12.
+fn days_in(year: u32) -> u32 {
+    if year >= 2025 {
+        12
+    } else {
+        25
+    }
+}
 
+// check_day exits with a usage error if the year doesn't have the day.
+fn check_day(year: u32, day: u32) {
+    if day > days_in(year) {
+        Args::command()
+            .error(
+                ErrorKind::ValueValidation,
+                format!(
+                    "{year} only has {} days, so there's no day {day}",
+                    days_in(year)
+                ),
+            )
+            .exit();
+    }
+}
+
+fn main() {
     let args = Args::parse();
-    println!(
-        "📩 Using input at path {}, for day {}, part {}\n",
-        args.input_path, args.day, args.part
-    );
-    let input = parse::get_input(args.input_path).unwrap();
+    match args.command {
+        Some(Command::Scaffold { year, day, title }) => {
+            check_day(year, day);
+            if let Err(e) = scaffold::scaffold(year, day, &title) {
+                eprintln!("❌ {e}");
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Examples {
+            year,
+            day,
+            page,
+            part2_example,
+        }) => {
+            check_day(year, day);
+            if let Err(e) = examples::examples(year, day, &page, part2_example) {
+                eprintln!("❌ {e}");
+                process::exit(1);
+            }
+            return;
+        }
+        Some(Command::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Args::command(), "aoc2025", &mut io::stdout());
+            return;
+        }
+        None => {}
+    }
+    logging::init(args.verbose, args.quiet, args.log_targets);
+    let config = config::Config::load(args.config.as_deref()).unwrap_or_else(|e| {
+        eprintln!("❌ {e}");
+        process::exit(1);
+    });
+    let format = args
+        .format
+        .or(config.format)
+        .unwrap_or(output::Format::Plain);
+
+    if args.list {
+        for registration in solution::registered() {
+            println!("{registration}");
+        }
+        return;
+    }
+
+    let year = args.year.or(config.year).unwrap_or(2025);
+    let day = args.day.unwrap();
+    check_day(year, day);
+    let Some(registration) = solution::find(year, day) else {
+        Args::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!("no solver registered for {year} day {day} (see --list)"),
+            )
+            .exit();
+    };
+    format.banner(&format!("📅 AoC {year}!"));
+    let solve = registration.solve;
+    let params = parse::Params::from([config.params(day), args.params].concat());
+    parallel::set_jobs(args.jobs.unwrap_or(1));
+    let reference = || {
+        registration.reference.as_ref().unwrap_or_else(|| {
+            Args::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    format!("{year} day {day} has no reference solver to check against"),
+                )
+                .exit()
+        })
+    };
+
+    if let Some(runs) = args.fuzz {
+        // Finding a disagreement is the point of fuzzing, so report it
+        // rather than panicking.
+        if let Err(e) = verify::fuzz(
+            solve,
+            reference(),
+            registration.parts,
+            runs,
+            args.seed,
+            &params,
+        ) {
+            eprintln!("❌ {e}");
+            process::exit(1);
+        }
+        format.banner(&format!("solver and reference agree on {runs} inputs"));
+        return;
+    }
+
+    if args.examples {
+        match fixtures::check(year, day) {
+            Ok(0) => println!("🤷 No examples for day {day} yet"),
+            Ok(checked) => println!("✅ {checked} example answers match"),
+            Err(e) => {
+                println!("❌ {e}");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    let Some(input_path) = args.input_path.or_else(|| config.input(year, day)) else {
+        Args::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "no --input-path given, and no inputs set in the config",
+            )
+            .exit();
+    };
+    if !input_path.exists() {
+        let session = config
+            .session()
+            .map(|s| format!(", with the session token in {}", s.display()))
+            .unwrap_or_default();
+        Args::command()
+            .error(
+                ErrorKind::Io,
+                format!(
+                    "no input at {}; get it from https://adventofcode.com/{year}/day/{day}/input{session}",
+                    input_path.display()
+                ),
+            )
+            .exit();
+    }
+    let parts = match args.part {
+        Some(part) if !registration.parts.contains(&part) => Args::command()
+            .error(
+                ErrorKind::InvalidValue,
+                format!("no solver registered for {year} day {day}, part {part}"),
+            )
+            .exit(),
+        Some(part) => vec![part],
+        None => registration.parts.to_vec(),
+    };
+    if let Some(runs) = args.bench {
+        let mut text = String::new();
+        parse::get_input(input_path)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        if let Err(e) = bench::bench(solve, &text, &parts, &params, runs, args.jobs.unwrap_or(0)) {
+            eprintln!("❌ {e}");
+            process::exit(1);
+        }
+        return;
+    }
+    if args.watch {
+        let args: Vec<String> = env::args().skip(1).collect();
+        watch::watch(
+            year,
+            day,
+            &parts,
+            &input_path,
+            &fixtures::dir(year, day),
+            args,
+        );
+        return;
+    }
+
+    format.banner(&format!(
+        "📩 Using input at path {}, for day {}, part{} {}\n",
+        input_path.display(),
+        day,
+        if parts.len() > 1 { "s" } else { "" },
+        parts
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(" and ")
+    ));
+    let mut input = parse::get_input(input_path).unwrap();
 
-    let solve = solutions
-        .get(&args.day)
-        .expect("no solver implemented for day!");
+    let start = Instant::now();
+    let answers = if args.verify {
+        let mut text = String::new();
+        input.read_to_string(&mut text).unwrap();
+        verify::verify(solve, reference(), &text, &parts, &params)
+    } else {
+        solve(input, &parts, &params)
+    };
 
-    solve(input, parse::Part::from(args.part))
+    let elapsed = start.elapsed();
+    let answers = answers.unwrap_or_else(|e| {
+        eprintln!("❌ {e}");
+        process::exit(1);
+    });
+    for (part, answer) in parts.into_iter().zip(answers) {
+        format.answer(year, day, part, &answer, elapsed);
+    }
 }
@@ -0,0 +1,52 @@
+// How answers get printed: with the usual banners, as just the answer, or as a
+// line of JSON for scripts. Logs go to stderr either way, so stdout only ever
+// has what the format asks for.
+
+use std::time::Duration;
+
+use crate::answer::Answer;
+use crate::parse;
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Format {
+    Plain,
+    Json,
+    Quiet,
+}
+
+impl Format {
+    // banner prints a line of flavour text, only in the plain format.
+    pub(crate) fn banner(&self, text: &str) {
+        if *self == Format::Plain {
+            println!("{text}");
+        }
+    }
+
+    // answer prints the answer to a day's part, along with how long it took.
+    // Parts solved together share their parsing, so the time is for the whole
+    // run rather than just this part.
+    pub(crate) fn answer(
+        &self,
+        year: u32,
+        day: u32,
+        part: parse::Part,
+        answer: &Answer,
+        elapsed: Duration,
+    ) {
+        match self {
+            Format::Plain => println!("part {part} output: {answer}"),
+            Format::Quiet => println!("{answer}"),
+            // The answer is a string, since it may not fit in a JSON number
+            // (or at least, in whatever parses it).
+            Format::Json => println!(
+                r#"{{"year": {year}, "day": {day}, "part": {}, "answer": "{answer}", "elapsed": {}}}"#,
+                match part {
+                    parse::Part::Part1 => 1,
+                    parse::Part::Part2 => 2,
+                },
+                elapsed.as_secs_f64()
+            ),
+        }
+    }
+}
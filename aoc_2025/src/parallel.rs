@@ -0,0 +1,44 @@
+// Spreading per-record work (ranges, banks...) across threads. It's opt in,
+// with --jobs, since most days are quick enough that starting threads costs
+// more than it saves. Results always come back in the same order as the
+// records, so answers come out the same however many jobs there are.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+static JOBS: AtomicUsize = AtomicUsize::new(1);
+
+// set_jobs sets how many threads map uses, with 0 meaning one per core.
+pub(crate) fn set_jobs(jobs: usize) {
+    let jobs = match jobs {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    JOBS.store(jobs, Ordering::Relaxed);
+}
+
+pub(crate) fn jobs() -> usize {
+    JOBS.load(Ordering::Relaxed)
+}
+
+// map applies f to each item, splitting the items into a contiguous chunk per
+// job, and returns the results in order.
+pub(crate) fn map<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let jobs = jobs().min(items.len());
+    if jobs <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let chunk = items.len().div_ceil(jobs);
+    let f = &f;
+    thread::scope(|s| {
+        let handles: Vec<_> = items
+            .chunks(chunk)
+            .map(|c| s.spawn(move || c.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
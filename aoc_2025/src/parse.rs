@@ -1,23 +1,73 @@
-use std::io::{Result, BufReader};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader};
 use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
 
-pub(crate) fn get_input(path: String) -> Result<BufReader<File>> {
+// Input is what solvers read their puzzle input from: usually a file, but
+// anything line-readable will do.
+pub(crate) type Input = Box<dyn BufRead>;
+
+pub(crate) fn get_input(path: impl AsRef<Path>) -> io::Result<Input> {
     let input = File::open(path)?;
     let reader = BufReader::new(input);
-    Ok(reader)
+    Ok(Box::new(reader))
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
 pub(crate) enum Part {
+    #[value(name = "1")]
     Part1,
+    #[value(name = "2")]
     Part2
 }
 
-impl From<String> for Part {
-    fn from(s: String) -> Part {
-        match s.as_str() {
-            "1" => Part::Part1,
-            "2" => Part::Part2,
-            _ => panic!("invalid part received")
+// PARTS is every part, for days that have both done.
+pub(crate) const PARTS: &[Part] = &[Part::Part1, Part::Part2];
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Part::Part1 => write!(f, "1"),
+            Part::Part2 => write!(f, "2"),
         }
     }
-}
\ No newline at end of file
+}
+
+// Params are the extra knobs a day can take on the command line, passed as
+// `--param key=value` (or just `--param key` for an on/off flag).
+#[derive(Debug, Default)]
+pub(crate) struct Params(HashMap<String, String>);
+
+impl From<Vec<String>> for Params {
+    fn from(args: Vec<String>) -> Params {
+        Params(args
+            .into_iter()
+            .map(|arg| match arg.split_once('=') {
+                Some((k, v)) => (k.to_string(), v.to_string()),
+                None => (arg, String::from("true")),
+            })
+            .collect())
+    }
+}
+
+impl Params {
+    // get_or parses the given param, falling back to the default if it wasn't
+    // passed.
+    pub(crate) fn get_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, String>
+    where
+        T::Err: fmt::Display,
+    {
+        match self.0.get(key) {
+            None => Ok(default),
+            Some(v) => v
+                .parse()
+                .map_err(|e| format!("invalid value {v:?} for param {key}: {e}")),
+        }
+    }
+
+    pub(crate) fn flag(&self, key: &str) -> Result<bool, String> {
+        self.get_or(key, false)
+    }
+}
@@ -0,0 +1,541 @@
+// Parsers for puzzle inputs, built up out of small pieces. A parser takes the
+// input from some position (a Span), and returns what it parsed along with the
+// rest of the input, or an Error saying where and why it couldn't.
+//
+// Parsers are plain closures, so days can write their own for anything the
+// pieces here don't cover.
+
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+use crate::parse;
+
+// Pos is a place in the input, as a line and column counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Pos {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+}
+
+impl Pos {
+    pub(crate) const START: Pos = Pos { line: 1, column: 1 };
+
+    // advance moves the position past the given bytes. Columns count
+    // characters, not bytes, so only the first byte of each one counts.
+    pub(crate) fn advance(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            if *b == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if b & 0xC0 != 0x80 {
+                self.column += 1;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+// Span is what's left of the input, and where it starts.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Span<'a> {
+    rest: &'a str,
+    pos: Pos,
+}
+
+impl<'a> Span<'a> {
+    pub(crate) fn new(text: &'a str, pos: Pos) -> Span<'a> {
+        Span { rest: text, pos }
+    }
+
+    // take splits off the first n bytes, returning them and what's after.
+    fn take(self, n: usize) -> (&'a str, Span<'a>) {
+        let (taken, rest) = self.rest.split_at(n);
+        let mut pos = self.pos;
+        pos.advance(taken.as_bytes());
+        (taken, Span { rest, pos })
+    }
+
+    // line_end skips a line ending, either "\n" or "\r\n", if there's one
+    // next in the input.
+    fn line_end(self) -> Option<Span<'a>> {
+        ["\n", "\r\n"]
+            .into_iter()
+            .find(|end| self.rest.starts_with(end))
+            .map(|end| self.take(end.len()).1)
+    }
+
+    // skip_while drops characters from the front for as long as they match.
+    fn skip_while(self, f: impl Fn(char) -> bool) -> Span<'a> {
+        let n = self.rest.len() - self.rest.trim_start_matches(f).len();
+        self.take(n).1
+    }
+
+    // found describes what's next in the input, for errors.
+    fn found(&self) -> String {
+        match self.rest.chars().next() {
+            None => String::from("end of input"),
+            Some('\n') => String::from("end of line"),
+            Some('\r') if self.rest.starts_with("\r\n") => String::from("end of line"),
+            Some(_) => {
+                let word: String = self
+                    .rest
+                    .chars()
+                    .take_while(|c| !c.is_whitespace())
+                    .take(12)
+                    .collect();
+                format!("{word:?}")
+            }
+        }
+    }
+
+    fn expected(&self, what: &str) -> Error {
+        Error {
+            pos: self.pos,
+            kind: Kind::Expected {
+                expected: vec![what.to_string()],
+                found: self.found(),
+            },
+        }
+    }
+
+    fn invalid(&self, reason: String) -> Error {
+        Error {
+            pos: self.pos,
+            kind: Kind::Invalid(reason),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    // The input didn't look like any of the things expected.
+    Expected {
+        expected: Vec<String>,
+        found: String,
+    },
+    // The input looked right, but something was wrong with it, like a number
+    // too big for its type.
+    Invalid(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Error {
+    pub(crate) pos: Pos,
+    kind: Kind,
+}
+
+impl Error {
+    // or picks whichever of two errors got further into the input, since
+    // that's the more useful one to report. If they got equally far, it's
+    // either one or the other that was expected.
+    fn or(self, other: Error) -> Error {
+        match (self.pos.cmp(&other.pos), self.kind, other.kind) {
+            (
+                std::cmp::Ordering::Equal,
+                Kind::Expected {
+                    mut expected,
+                    found,
+                },
+                Kind::Expected { expected: more, .. },
+            ) => {
+                expected.extend(more);
+                Error {
+                    pos: self.pos,
+                    kind: Kind::Expected { expected, found },
+                }
+            }
+            (std::cmp::Ordering::Less, _, kind) => Error {
+                pos: other.pos,
+                kind,
+            },
+            (_, kind, _) => Error {
+                pos: self.pos,
+                kind,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            Kind::Expected { expected, found } => {
+                let expected = match expected.split_last() {
+                    Some((last, rest)) if !rest.is_empty() => {
+                        format!("{} or {last}", rest.join(", "))
+                    }
+                    _ => expected.join(""),
+                };
+                write!(f, "{}: expected {expected}, found {found}", self.pos)
+            }
+            Kind::Invalid(reason) => write!(f, "{}: {reason}", self.pos),
+        }
+    }
+}
+
+impl From<Error> for String {
+    fn from(e: Error) -> String {
+        e.to_string()
+    }
+}
+
+pub(crate) type Parsed<'a, T> = Result<(T, Span<'a>), Error>;
+
+pub(crate) trait Parser<'a, T>: Fn(Span<'a>) -> Parsed<'a, T> {}
+
+impl<'a, T, F: Fn(Span<'a>) -> Parsed<'a, T>> Parser<'a, T> for F {}
+
+// tag parses exactly the given token.
+pub(crate) fn tag<'a>(token: &'static str) -> impl Parser<'a, &'a str> {
+    move |input: Span<'a>| {
+        if input.rest.starts_with(token) {
+            Ok(input.take(token.len()))
+        } else {
+            Err(input.expected(&format!("{token:?}")))
+        }
+    }
+}
+
+// int parses a whole number, with an optional sign.
+pub(crate) fn int<'a, T: FromStr>() -> impl Parser<'a, T>
+where
+    T::Err: fmt::Display,
+{
+    move |input: Span<'a>| {
+        let bytes = input.rest.as_bytes();
+        let sign = usize::from(matches!(bytes.first(), Some(b'-' | b'+')));
+        let digits = bytes[sign..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits == 0 {
+            return Err(input.expected("a number"));
+        }
+        let (text, rest) = input.take(sign + digits);
+        let n = text
+            .parse()
+            .map_err(|e| input.invalid(format!("can't parse {text:?}: {e}")))?;
+        Ok((n, rest))
+    }
+}
+
+// range parses a `low-high` range. Either end can be negative, as in `-5--3`,
+// if the type allows it.
+pub(crate) fn range<'a, T: FromStr>() -> impl Parser<'a, (T, T)>
+where
+    T::Err: fmt::Display,
+{
+    pair(int(), preceded(tag("-"), int()))
+}
+
+// pair parses one thing, then another.
+pub(crate) fn pair<'a, A, B>(
+    first: impl Parser<'a, A>,
+    second: impl Parser<'a, B>,
+) -> impl Parser<'a, (A, B)> {
+    move |input: Span<'a>| {
+        let (a, input) = first(input)?;
+        let (b, input) = second(input)?;
+        Ok(((a, b), input))
+    }
+}
+
+// preceded parses one thing, then another, keeping just the second.
+pub(crate) fn preceded<'a, A, B>(
+    first: impl Parser<'a, A>,
+    second: impl Parser<'a, B>,
+) -> impl Parser<'a, B> {
+    map(pair(first, second), |(_, b)| b)
+}
+
+// alt tries the first parser, then the second if that doesn't work.
+pub(crate) fn alt<'a, T>(
+    first: impl Parser<'a, T>,
+    second: impl Parser<'a, T>,
+) -> impl Parser<'a, T> {
+    move |input: Span<'a>| first(input).or_else(|e| second(input).map_err(|other| e.or(other)))
+}
+
+pub(crate) fn map<'a, T, U>(parser: impl Parser<'a, T>, f: impl Fn(T) -> U) -> impl Parser<'a, U> {
+    move |input: Span<'a>| parser(input).map(|(t, rest)| (f(t), rest))
+}
+
+// try_map is map for conversions that can fail, with the error pointing at
+// the start of what was parsed.
+pub(crate) fn try_map<'a, T, U>(
+    parser: impl Parser<'a, T>,
+    f: impl Fn(T) -> Result<U, String>,
+) -> impl Parser<'a, U> {
+    move |input: Span<'a>| {
+        let (t, rest) = parser(input)?;
+        Ok((f(t).map_err(|e| input.invalid(e))?, rest))
+    }
+}
+
+// value parses something, but returns the given value instead, for things
+// like turning a token into an enum.
+pub(crate) fn value<'a, T: Clone, U>(parser: impl Parser<'a, U>, v: T) -> impl Parser<'a, T> {
+    map(parser, move |_| v.clone())
+}
+
+// padded allows spaces (but not newlines) either side of something.
+pub(crate) fn padded<'a, T>(parser: impl Parser<'a, T>) -> impl Parser<'a, T> {
+    let space = |c| c == ' ' || c == '\t' || c == '\r';
+    move |input: Span<'a>| {
+        let (t, rest) = parser(input.skip_while(space))?;
+        Ok((t, rest.skip_while(space)))
+    }
+}
+
+// separated parses one or more things with separators between them. Once a
+// separator's been parsed, another item has to follow it.
+pub(crate) fn separated<'a, T, S>(
+    item: impl Parser<'a, T>,
+    separator: impl Parser<'a, S>,
+) -> impl Parser<'a, Vec<T>> {
+    move |input: Span<'a>| {
+        let (first, mut input) = item(input)?;
+        let mut items = vec![first];
+        while let Ok((_, rest)) = separator(input) {
+            let (next, rest) = item(rest)?;
+            items.push(next);
+            input = rest;
+        }
+        Ok((items, input))
+    }
+}
+
+// lines parses one or more lines, each with the given parser, up to the end
+// of the input or a blank line (which is left for whatever comes next). Lines
+// can end with "\n" or "\r\n".
+pub(crate) fn lines<'a, T>(line: impl Parser<'a, T>) -> impl Parser<'a, Vec<T>> {
+    move |mut input: Span<'a>| {
+        let mut items = Vec::new();
+        loop {
+            let (item, rest) = line(input)?;
+            items.push(item);
+            if rest.rest.is_empty() {
+                return Ok((items, rest));
+            }
+            input = rest
+                .line_end()
+                .ok_or_else(|| rest.expected("end of line"))?;
+            if input.rest.is_empty() || input.line_end().is_some() {
+                return Ok((items, input));
+            }
+        }
+    }
+}
+
+// sections parses a section, then a blank line, then another section.
+pub(crate) fn sections<'a, A, B>(
+    first: impl Parser<'a, A>,
+    second: impl Parser<'a, B>,
+) -> impl Parser<'a, (A, B)> {
+    let blank_line = |input: Span<'a>| match input.line_end() {
+        Some(rest) => Ok(((), rest)),
+        None => Err(input.expected("a blank line")),
+    };
+    pair(first, preceded(blank_line, second))
+}
+
+// grid parses lines of characters into rows of cells, up to the end of the
+// input or a blank line. Rows can be different lengths, so days that need a
+// rectangle check for themselves.
+pub(crate) fn grid<'a, T>(
+    cell: impl Fn(char) -> Result<T, String>,
+) -> impl Parser<'a, Vec<Vec<T>>> {
+    lines(move |mut input: Span<'a>| {
+        let mut row = Vec::new();
+        while let Some(c) = input.rest.chars().next() {
+            if input.line_end().is_some() {
+                break;
+            }
+            row.push(cell(c).map_err(|e| input.invalid(e))?);
+            input = input.take(c.len_utf8()).1;
+        }
+        Ok((row, input))
+    })
+}
+
+// parse_at runs the parser over the whole text, which starts at the given
+// position in the input. Anything but whitespace left over is an error.
+pub(crate) fn parse_at<'a, T>(
+    parser: impl Parser<'a, T>,
+    text: &'a str,
+    pos: Pos,
+) -> Result<T, Error> {
+    let (t, rest) = parser(Span::new(text, pos))?;
+    let rest = rest.skip_while(char::is_whitespace);
+    if !rest.rest.is_empty() {
+        return Err(rest.expected("end of input"));
+    }
+    Ok(t)
+}
+
+pub(crate) fn parse_all<'a, T>(parser: impl Parser<'a, T>, text: &'a str) -> Result<T, Error> {
+    parse_at(parser, text, Pos::START)
+}
+
+// read reads in the whole input, for parsing with parse_all. Days with big
+// inputs can read it a bit at a time with the stream module instead, and
+// parse each piece with parse_at.
+pub(crate) fn read(mut input: parse::Input) -> Result<String, String> {
+    let mut text = String::new();
+    input.read_to_string(&mut text).map_err(|e| e.to_string())?;
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(line: usize, column: usize) -> Pos {
+        Pos { line, column }
+    }
+
+    fn expected(at: Pos, expected: &[&str], found: &str) -> Error {
+        Error {
+            pos: at,
+            kind: Kind::Expected {
+                expected: expected.iter().map(|e| e.to_string()).collect(),
+                found: found.to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn advance_counts_lines_and_characters() {
+        let mut p = Pos::START;
+        p.advance(b"abc");
+        assert_eq!(p, pos(1, 4));
+        p.advance(b"de\nf");
+        assert_eq!(p, pos(2, 2));
+        p.advance(b"\n\n");
+        assert_eq!(p, pos(4, 1));
+
+        // Multibyte characters are one column each, however many bytes.
+        let mut p = Pos::START;
+        p.advance("é€😀x".as_bytes());
+        assert_eq!(p, pos(1, 5));
+
+        // Positions in errors point past them too.
+        let e = parse_all(preceded(tag("é€"), int::<u8>()), "é€x").unwrap_err();
+        assert_eq!(e.pos, pos(1, 3));
+    }
+
+    #[test]
+    fn or_keeps_the_error_that_got_furthest() {
+        let near = expected(pos(1, 2), &["a"], "\"x\"");
+        let far = expected(pos(2, 1), &["b"], "\"y\"");
+        assert_eq!(near.clone().or(far.clone()), far);
+        assert_eq!(far.clone().or(near), far);
+
+        // Errors from the same place are merged, keeping the first's found.
+        let other = expected(pos(2, 1), &["c", "d"], "\"z\"");
+        let merged = far.or(other);
+        assert_eq!(merged, expected(pos(2, 1), &["b", "c", "d"], "\"y\""));
+        assert_eq!(
+            merged.to_string(),
+            "line 2, column 1: expected b, c or d, found \"y\""
+        );
+
+        // An invalid error can't be merged, so the first one's kept.
+        let invalid = Span::new("", pos(2, 1)).invalid(String::from("too big"));
+        assert_eq!(invalid.clone().or(merged.clone()), invalid);
+        assert_eq!(merged.clone().or(invalid), merged);
+    }
+
+    #[test]
+    fn alt_reports_every_choice() {
+        let p = alt(value(tag("L"), -1), value(tag("R"), 1));
+        assert_eq!(parse_all(&p, "R").unwrap(), 1);
+        assert_eq!(
+            parse_all(&p, "X").unwrap_err().to_string(),
+            "line 1, column 1: expected \"L\" or \"R\", found \"X\""
+        );
+    }
+
+    #[test]
+    fn separated_edges() {
+        let list = || separated(int::<u32>(), tag(","));
+        assert_eq!(parse_all(list(), "7").unwrap(), vec![7]);
+        assert_eq!(parse_all(list(), "1,2,3").unwrap(), vec![1, 2, 3]);
+
+        // There has to be at least one item, and one after every separator.
+        assert_eq!(parse_all(list(), "").unwrap_err().pos, pos(1, 1));
+        assert_eq!(
+            parse_all(list(), "1,2,").unwrap_err().to_string(),
+            "line 1, column 5: expected a number, found end of input"
+        );
+
+        // Anything else stops the list, and is left for what comes next.
+        let (items, rest) = list()(Span::new("1,2;3", Pos::START)).unwrap();
+        assert_eq!(items, vec![1, 2]);
+        assert_eq!((rest.rest, rest.pos), (";3", pos(1, 4)));
+    }
+
+    #[test]
+    fn lines_edges() {
+        let numbers = || lines(int::<u32>());
+        assert_eq!(parse_all(numbers(), "1").unwrap(), vec![1]);
+        assert_eq!(parse_all(numbers(), "1\n2\n").unwrap(), vec![1, 2]);
+        assert_eq!(parse_all(numbers(), "1\r\n2\r\n").unwrap(), vec![1, 2]);
+
+        // A blank line ends them, and is left for whatever's next.
+        for text in ["1\n2\n\n3", "1\r\n2\r\n\r\n3"] {
+            let (items, rest) = numbers()(Span::new(text, Pos::START)).unwrap();
+            assert_eq!(items, vec![1, 2]);
+            assert_eq!(rest.pos, pos(3, 1));
+        }
+
+        // Something left on a line is an error, even a lone \r.
+        assert_eq!(
+            parse_all(numbers(), "1\n2x\n").unwrap_err().to_string(),
+            "line 2, column 2: expected end of line, found \"x\""
+        );
+        assert_eq!(parse_all(numbers(), "1\r2").unwrap_err().pos, pos(1, 2));
+        assert_eq!(parse_all(numbers(), "").unwrap_err().pos, pos(1, 1));
+    }
+
+    #[test]
+    fn sections_edges() {
+        let both = || sections(lines(int::<u32>()), lines(int::<u32>()));
+        assert_eq!(
+            parse_all(both(), "1\n2\n\n3\n").unwrap(),
+            (vec![1, 2], vec![3])
+        );
+        assert_eq!(parse_all(both(), "1\r\n\r\n3").unwrap(), (vec![1], vec![3]));
+
+        assert_eq!(
+            parse_all(both(), "1\n2").unwrap_err().to_string(),
+            "line 2, column 2: expected a blank line, found end of input"
+        );
+        // Only one blank line goes between them.
+        assert_eq!(
+            parse_all(both(), "1\n\n\n3").unwrap_err().to_string(),
+            "line 3, column 1: expected a number, found end of line"
+        );
+        assert_eq!(parse_all(both(), "1\n\n").unwrap_err().pos, pos(3, 1));
+    }
+
+    #[test]
+    fn grid_ignores_line_endings() {
+        let cells = || grid(|c| Ok(c == '#'));
+        let want = vec![vec![true, false], vec![false, true]];
+        assert_eq!(parse_all(cells(), "#.\n.#\n").unwrap(), want);
+        assert_eq!(parse_all(cells(), "#.\r\n.#\r\n").unwrap(), want);
+
+        let digits = grid(|c| c.to_digit(10).ok_or(format!("{c:?} isn't a digit")));
+        assert_eq!(
+            parse_all(digits, "12\n3é4").unwrap_err().to_string(),
+            "line 2, column 2: 'é' isn't a digit"
+        );
+    }
+}
@@ -0,0 +1,89 @@
+// Scaffolding for a new day: a module to fill in, registered and ready to
+// run, plus a fixtures directory for its examples. 2025 days are modules in
+// this crate; 2024 days are each their own little crate, reading stdin.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::fixtures;
+
+const DAY_2025: &str = include_str!("../templates/day.rs.tmpl");
+const MAIN_2024: &str = include_str!("../templates/2024_main.rs.tmpl");
+const CARGO_2024: &str = include_str!("../templates/2024_Cargo.toml.tmpl");
+
+fn fill(template: &str, day: u32, title: &str) -> String {
+    template
+        .replace("@DAY@", &day.to_string())
+        .replace("@TITLE@", title)
+}
+
+// create writes a new file, refusing to clobber one that's already there.
+fn create(path: &Path, contents: &str) -> Result<(), String> {
+    if path.exists() {
+        return Err(format!("{path:?} already exists"));
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("can't create {parent:?}: {e}"))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("can't write {path:?}: {e}"))?;
+    println!("created {}", path.display());
+    Ok(())
+}
+
+fn create_dir(path: &Path) -> Result<(), String> {
+    fs::create_dir_all(path).map_err(|e| format!("can't create {path:?}: {e}"))?;
+    println!("created {}/", path.display());
+    Ok(())
+}
+
+// register adds `mod dayNN;` to main.rs, keeping the day modules in order.
+fn register(main: &Path, module: &str) -> Result<(), String> {
+    let text = fs::read_to_string(main).map_err(|e| format!("can't read {main:?}: {e}"))?;
+    let line = format!("mod {module};");
+    let mut lines: Vec<&str> = text.lines().collect();
+    if lines.contains(&line.as_str()) {
+        return Ok(());
+    }
+    let is_day = |l: &&str| l.starts_with("mod day") && l.ends_with(';');
+    let at = match lines.iter().rposition(is_day) {
+        Some(last) => lines[..=last]
+            .iter()
+            .position(|l| is_day(l) && *l > line.as_str())
+            .unwrap_or(last + 1),
+        None => return Err(format!("can't find the day modules in {main:?}")),
+    };
+    lines.insert(at, &line);
+    fs::write(main, lines.join("\n") + "\n").map_err(|e| format!("can't write {main:?}: {e}"))?;
+    println!("registered {module} in {}", main.display());
+    Ok(())
+}
+
+pub(crate) fn scaffold(year: u32, day: u32, title: &str) -> Result<(), String> {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    match year {
+        2025 => {
+            let module = format!("day{day:02}");
+            create(
+                &crate_dir.join("src").join(format!("{module}.rs")),
+                &fill(DAY_2025, day, title),
+            )?;
+            register(&crate_dir.join("src").join("main.rs"), &module)?;
+            create_dir(&fixtures::dir(year, day))
+        }
+        2024 => {
+            let day_dir: PathBuf = crate_dir
+                .parent()
+                .unwrap()
+                .join("aoc_2024")
+                .join(format!("{day:02}"))
+                .join("rust");
+            create(&day_dir.join("Cargo.toml"), CARGO_2024)?;
+            create(
+                &day_dir.join("src").join("main.rs"),
+                &fill(MAIN_2024, day, title),
+            )?;
+            create_dir(&fixtures::dir(year, day))
+        }
+        _ => Err(format!("don't know how to scaffold {year}")),
+    }
+}
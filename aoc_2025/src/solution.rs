@@ -0,0 +1,79 @@
+// A Solution is a day's input parsed into whatever model suits it, which both
+// parts are then answered from. That way a day which does the heavy lifting
+// up front (sorting distances, tracing beams...) only does it once, however
+// many parts get asked for.
+
+use std::fmt;
+
+use crate::answer::Answer;
+use crate::parse;
+use crate::verify;
+use crate::Solver;
+
+pub(crate) trait Solution: Sized {
+    fn parse(input: parse::Input, params: &parse::Params) -> Result<Self, String>;
+    fn part1(&self, params: &parse::Params) -> Result<Answer, String>;
+    fn part2(&self, params: &parse::Params) -> Result<Answer, String>;
+}
+
+// solve parses the input once, and answers each of the given parts from it, in
+// order.
+pub(crate) fn solve<S: Solution>(
+    input: parse::Input,
+    parts: &[parse::Part],
+    params: &parse::Params,
+) -> Result<Vec<Answer>, String> {
+    let solution = S::parse(input, params)?;
+    parts
+        .iter()
+        .map(|part| match part {
+            parse::Part::Part1 => solution.part1(params),
+            parse::Part::Part2 => solution.part2(params),
+        })
+        .collect()
+}
+
+// Registration is a day's entry in the list of solvers. Each day submits its
+// own with inventory::submit!, so the binary finds them without a list of
+// days to keep up to date.
+pub(crate) struct Registration {
+    pub(crate) year: u32,
+    pub(crate) day: u32,
+    pub(crate) title: &'static str,
+    pub(crate) parts: &'static [parse::Part],
+    pub(crate) solve: Solver,
+    pub(crate) reference: Option<verify::Reference>,
+}
+
+inventory::collect!(Registration);
+
+impl fmt::Display for Registration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts: Vec<String> = self.parts.iter().map(|p| p.to_string()).collect();
+        write!(
+            f,
+            "{} day {:>2}: {} (parts {})",
+            self.year,
+            self.day,
+            self.title,
+            parts.join(", ")
+        )?;
+        if self.reference.is_some() {
+            write!(f, ", with a reference solver")?;
+        }
+        Ok(())
+    }
+}
+
+// registered returns every registered solver, in order of year and day.
+pub(crate) fn registered() -> Vec<&'static Registration> {
+    let mut registrations: Vec<_> = inventory::iter::<Registration>.into_iter().collect();
+    registrations.sort_by_key(|r| (r.year, r.day));
+    registrations
+}
+
+pub(crate) fn find(year: u32, day: u32) -> Option<&'static Registration> {
+    inventory::iter::<Registration>
+        .into_iter()
+        .find(|r| r.year == year && r.day == day)
+}
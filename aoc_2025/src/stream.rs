@@ -0,0 +1,151 @@
+// Reading input a bit at a time, rather than into one big string, so that
+// memory stays bounded by the longest line (or token) however big the input
+// gets. Inputs come in two shapes: sections of lines separated by blank lines
+// (Sections), and lists split on some separators (Tokens).
+
+use std::io::BufRead;
+
+use crate::parse;
+use crate::parser::Pos;
+
+// Sections reads the input a line at a time, handing out each blank-line
+// separated section in turn.
+pub(crate) struct Sections {
+    input: parse::Input,
+    line: usize,
+    at_end: bool,
+    in_section: bool,
+}
+
+impl Sections {
+    pub(crate) fn new(input: parse::Input) -> Sections {
+        Sections {
+            input,
+            line: 0,
+            at_end: false,
+            in_section: false,
+        }
+    }
+
+    // read_line reads the next line, without its newline, or None at the end
+    // of the input.
+    fn read_line(&mut self) -> Result<Option<String>, String> {
+        let mut line = String::new();
+        let read = self
+            .input
+            .read_line(&mut line)
+            .map_err(|e| format!("line {}: {e}", self.line + 1))?;
+        if read == 0 {
+            self.at_end = true;
+            return Ok(None);
+        }
+        self.line += 1;
+        line.truncate(line.trim_end_matches(['\n', '\r']).len());
+        Ok(Some(line))
+    }
+
+    // section returns the lines of the next section, or None once the input
+    // has run out. Whatever's left of the previous section is skipped.
+    pub(crate) fn section(&mut self) -> Option<Section<'_>> {
+        if self.in_section {
+            Section(self).for_each(drop);
+        }
+        if self.at_end {
+            return None;
+        }
+        self.in_section = true;
+        Some(Section(self))
+    }
+}
+
+// Section is an iterator over the lines of one section, along with where each
+// starts (for parser::parse_at), ending at a blank line or the end of the
+// input.
+pub(crate) struct Section<'a>(&'a mut Sections);
+
+impl Iterator for Section<'_> {
+    type Item = Result<(Pos, String), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.0.in_section {
+            return None;
+        }
+        match self.0.read_line() {
+            Ok(Some(line)) if !line.is_empty() => Some(Ok((
+                Pos {
+                    line: self.0.line,
+                    column: 1,
+                },
+                line,
+            ))),
+            Ok(_) => {
+                self.0.in_section = false;
+                None
+            }
+            Err(e) => {
+                self.0.in_section = false;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// Tokens reads the input a token at a time, splitting it on any of the
+// separators, and yields each token along with where it starts.
+pub(crate) struct Tokens {
+    input: parse::Input,
+    separators: &'static [u8],
+    pos: Pos,
+}
+
+impl Tokens {
+    pub(crate) fn new(input: parse::Input, separators: &'static [u8]) -> Tokens {
+        Tokens {
+            input,
+            separators,
+            pos: Pos::START,
+        }
+    }
+}
+
+impl Iterator for Tokens {
+    type Item = Result<(Pos, String), String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.pos;
+        let mut token = Vec::new();
+        loop {
+            let buf = match self.input.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) => return Some(Err(format!("{}: {e}", self.pos))),
+            };
+            if buf.is_empty() {
+                // Nothing read since the last separator means the input's
+                // done, rather than an empty token at the end of it.
+                if self.pos == start {
+                    return None;
+                }
+                break;
+            }
+            match buf.iter().position(|b| self.separators.contains(b)) {
+                Some(i) => {
+                    token.extend_from_slice(&buf[..i]);
+                    self.pos.advance(&buf[..=i]);
+                    self.input.consume(i + 1);
+                    break;
+                }
+                None => {
+                    let read = buf.len();
+                    token.extend_from_slice(buf);
+                    self.pos.advance(buf);
+                    self.input.consume(read);
+                }
+            }
+        }
+        Some(
+            String::from_utf8(token)
+                .map(|token| (start, token))
+                .map_err(|e| format!("{start}: {e}")),
+        )
+    }
+}
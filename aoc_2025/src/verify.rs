@@ -0,0 +1,120 @@
+// Cross-checking clever solvers against slow-but-obvious reference ones,
+// either on a real input (--verify), or on lots of small random inputs
+// (--fuzz).
+
+use std::io::Cursor;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::answer::Answer;
+use crate::parse;
+use crate::Solver;
+
+// Rng is a tiny xorshift generator; plenty random enough to make up puzzle
+// inputs, and seedable so a failing input can be reproduced.
+#[derive(Debug)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // between returns a number in [low, high].
+    pub(crate) fn between(&mut self, low: u64, high: u64) -> u64 {
+        low + self.next() % (high - low + 1)
+    }
+
+    pub(crate) fn chance(&mut self, percent: u64) -> bool {
+        self.between(1, 100) <= percent
+    }
+}
+
+// Reference is a day's slow but obviously correct solver, which answers one
+// part at a time, plus a generator for inputs small enough for it to handle.
+pub(crate) struct Reference {
+    pub(crate) solve: fn(parse::Input, parse::Part, &parse::Params) -> Result<Answer, String>,
+    pub(crate) random_input: fn(&mut Rng) -> String,
+}
+
+fn input_from(text: &str) -> parse::Input {
+    Box::new(Cursor::new(text.to_string()))
+}
+
+// verify checks the solver against the reference on the given input, for
+// each of the given parts, and returns the solver's answers if they agree.
+pub(crate) fn verify(
+    solve: Solver,
+    reference: &Reference,
+    text: &str,
+    parts: &[parse::Part],
+    params: &parse::Params,
+) -> Result<Vec<Answer>, String> {
+    let answers = solve(input_from(text), parts, params)?;
+    for (part, answer) in parts.iter().zip(&answers) {
+        let expected = (reference.solve)(input_from(text), *part, params)?;
+        if *answer != expected {
+            return Err(format!(
+                "{part:?}: solver says {answer}, but the reference says {expected}"
+            ));
+        }
+    }
+    Ok(answers)
+}
+
+// fuzz checks the solver against the reference on `runs` random inputs, for
+// each of the given parts, stopping at the first disagreement.
+pub(crate) fn fuzz(
+    solve: Solver,
+    reference: &Reference,
+    parts: &[parse::Part],
+    runs: usize,
+    seed: Option<u64>,
+    params: &parse::Params,
+) -> Result<(), String> {
+    let seed = seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64
+    });
+    log::debug!("fuzzing {runs} inputs with seed {seed}");
+
+    let mut rng = Rng::new(seed);
+    for run in 0..runs {
+        let text = (reference.random_input)(&mut rng);
+        verify(solve, reference, &text, parts, params)
+            .map_err(|e| format!("run {run} (seed {seed}), {e}, for input:\n{text}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    #[test]
+    fn solvers_agree_with_their_references() {
+        for registration in solution::registered() {
+            let Some(reference) = &registration.reference else {
+                continue;
+            };
+            fuzz(
+                registration.solve,
+                reference,
+                registration.parts,
+                200,
+                Some(2025),
+                &parse::Params::default(),
+            )
+            .unwrap_or_else(|e| panic!("{registration}: {e}"));
+        }
+    }
+}
@@ -0,0 +1,129 @@
+// Watch mode: keep an eye on the source, the day's fixtures and its input,
+// and whenever any of them change, check the examples and then solve the
+// input again, pointing out any answers that changed. Each run is a fresh
+// `cargo run`, so that source changes get rebuilt.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::parse;
+
+const POLL: Duration = Duration::from_millis(500);
+
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+// snapshot records when each file under the given paths was last modified.
+// Missing paths are fine; they just show up once they exist.
+fn snapshot(paths: &[PathBuf]) -> Snapshot {
+    fn walk(path: &Path, into: &mut Snapshot) {
+        if path.is_dir() {
+            for entry in fs::read_dir(path).into_iter().flatten().flatten() {
+                walk(&entry.path(), into);
+            }
+        } else if let Ok(modified) = fs::metadata(path).and_then(|m| m.modified()) {
+            into.insert(path.to_path_buf(), modified);
+        }
+    }
+    let mut snapshot = Snapshot::new();
+    for path in paths {
+        walk(path, &mut snapshot);
+    }
+    snapshot
+}
+
+// cargo_run runs this binary with the given args, rebuilding it first if
+// needed, and returns its output if it succeeded. Build errors and logs go
+// straight through to stderr.
+fn cargo_run(args: &[String]) -> Option<String> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| String::from("cargo"));
+    let output = Command::new(cargo)
+        .args(["run", "--quiet", "--manifest-path"])
+        .arg(Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml"))
+        .arg("--")
+        .args(args)
+        .stderr(Stdio::inherit())
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+    match output.status.success() {
+        true => Some(stdout),
+        false => {
+            print!("{stdout}");
+            None
+        }
+    }
+}
+
+// watch re-runs the day whenever anything it depends on changes. `args` are
+// the ones this run was started with, which get passed along (minus --watch)
+// to solve the input.
+pub(crate) fn watch(
+    year: u32,
+    day: u32,
+    parts: &[parse::Part],
+    input: &Path,
+    fixtures: &Path,
+    args: Vec<String>,
+) {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let watched = [
+        crate_dir.join("src"),
+        crate_dir.join("Cargo.toml"),
+        fixtures.to_path_buf(),
+        input.to_path_buf(),
+    ];
+    println!(
+        "👀 Watching {}, {} and {}, for day {day} (ctrl-c to stop)",
+        crate_dir.join("src").display(),
+        fixtures.display(),
+        input.display()
+    );
+
+    let examples = [
+        "--year",
+        &year.to_string(),
+        "--day",
+        &day.to_string(),
+        "--examples",
+        "--format",
+        "quiet",
+    ]
+    .map(String::from);
+    let mut solve: Vec<String> = args.into_iter().filter(|a| a != "--watch").collect();
+    solve.extend(["--format", "quiet"].map(String::from));
+
+    let mut seen = Snapshot::new();
+    let mut answers: Vec<String> = Vec::new();
+    loop {
+        let now = snapshot(&watched);
+        if now == seen {
+            thread::sleep(POLL);
+            continue;
+        }
+        seen = now;
+
+        println!("\n🔁 Running day {day}...");
+        match cargo_run(&examples) {
+            Some(output) => print!("{output}"),
+            None => continue,
+        }
+        let Some(output) = cargo_run(&solve) else {
+            continue;
+        };
+        let latest: Vec<String> = output.lines().map(String::from).collect();
+        for (i, (part, answer)) in parts.iter().zip(&latest).enumerate() {
+            match answers.get(i) {
+                Some(before) if before != answer => {
+                    println!("part {part} output: {answer} (changed from {before})")
+                }
+                _ => println!("part {part} output: {answer}"),
+            }
+        }
+        answers = latest;
+    }
+}